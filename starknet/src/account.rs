@@ -0,0 +1,269 @@
+use crate::crypto::{self, pedersen::PedersenHasher, HasherTrait};
+use crate::status::AppSW;
+use crate::types::{FieldElement, U256};
+
+extern crate alloc;
+use alloc::{vec, vec::Vec};
+
+pub const OPENZEPPELIN_0_8_1: &str =
+    "061dac032f228abef9c6626f995015233097ae253a7f72d68552db02f2971b8f";
+pub const ARGENT_0_3_0: &str = "01a736d6ed154502257f02b1ccdf4d9d1089f80811cd6acad48e6b6a9d1f2003";
+pub const ARGENT_0_3_1: &str = "029927c8af6bccf3f6fda035981e765a7bdbf18a2dc0d630494f8758aa908e2b";
+pub const ARGENT_0_4_0: &str = "036078334509b514626504edc9fb252328d1a240e4e948bef8d0c08dff45927f";
+/// Braavos accounts are deployed with the base account class, upgraded on deployment
+pub const BRAAVOS_BASE: &str = "03d16c7a9a60b0593bd202f660a28c5d76e0403601d9ccc7e4fa253b6a70c201";
+
+/// Layout of the constructor calldata of an account class
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConstructorLayout {
+    /// (public_key)
+    PublicKey,
+    /// (owner, guardian), the guardian being zero if none
+    OwnerGuardian,
+    /// (owner: Signer, guardian: Option<Signer>)
+    Signers,
+}
+
+#[derive(Debug)]
+pub struct AccountClassInfo {
+    pub class_hash: &'static str,
+    pub name: &'static str,
+    pub layout: ConstructorLayout,
+}
+
+pub const NB_ACCOUNT_CLASSES: usize = 5;
+
+pub const ACCOUNT_CLASSES: [AccountClassInfo; NB_ACCOUNT_CLASSES] = [
+    AccountClassInfo {
+        class_hash: OPENZEPPELIN_0_8_1,
+        name: "OpenZeppelin account v0.8.1",
+        layout: ConstructorLayout::PublicKey,
+    },
+    AccountClassInfo {
+        class_hash: ARGENT_0_3_0,
+        name: "Argent account v0.3.0",
+        layout: ConstructorLayout::OwnerGuardian,
+    },
+    AccountClassInfo {
+        class_hash: ARGENT_0_3_1,
+        name: "Argent account v0.3.1",
+        layout: ConstructorLayout::OwnerGuardian,
+    },
+    AccountClassInfo {
+        class_hash: ARGENT_0_4_0,
+        name: "Argent account v0.4",
+        layout: ConstructorLayout::Signers,
+    },
+    AccountClassInfo {
+        class_hash: BRAAVOS_BASE,
+        name: "Braavos account",
+        layout: ConstructorLayout::PublicKey,
+    },
+];
+
+/// Account class deployed with `class_hash`, if known
+pub fn get_account_class(class_hash: &FieldElement) -> Option<&'static AccountClassInfo> {
+    ACCOUNT_CLASSES
+        .iter()
+        .find(|class| FieldElement::from(class.class_hash) == *class_hash)
+}
+
+/// Signer of an account, see the Signer enum of the Argent account
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Signer {
+    /// Stark public key
+    Starknet(FieldElement),
+    /// Hash of the secp256k1 public key
+    Secp256k1(FieldElement),
+    /// secp256r1 public key x coordinate
+    Secp256r1(U256),
+    /// Ethereum address
+    Eip191(FieldElement),
+}
+
+impl Signer {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Signer::Starknet(_) => "Starknet",
+            Signer::Secp256k1(_) => "Secp256k1",
+            Signer::Secp256r1(_) => "Secp256r1",
+            Signer::Eip191(_) => "Ethereum",
+        }
+    }
+
+    /// Decodes a serialized Signer (variant index followed by its data), returns it with
+    /// the remaining calldata. Webauthn signers are not supported.
+    fn decode(calldata: &[FieldElement]) -> Option<(Self, &[FieldElement])> {
+        let (variant, data) = calldata.split_first()?;
+        if *variant > FieldElement::THREE {
+            return None;
+        }
+        match (u8::from(*variant), data) {
+            (0, [key, rest @ ..]) => Some((Signer::Starknet(*key), rest)),
+            (1, [key_hash, rest @ ..]) => Some((Signer::Secp256k1(*key_hash), rest)),
+            (2, [low, high, rest @ ..]) => {
+                Some((Signer::Secp256r1(U256::from_limbs(*low, *high)?), rest))
+            }
+            (3, [address, rest @ ..]) => Some((Signer::Eip191(*address), rest)),
+            _ => None,
+        }
+    }
+}
+
+/// Decoded constructor calldata of an account
+#[derive(Debug)]
+pub struct AccountConstructor {
+    pub owner: Signer,
+    pub guardian: Option<Signer>,
+}
+
+impl AccountConstructor {
+    /// Stark public key owning the account, None if its owner is another kind of signer
+    pub fn owner_public_key(&self) -> Option<FieldElement> {
+        match self.owner {
+            Signer::Starknet(public_key) => Some(public_key),
+            _ => None,
+        }
+    }
+}
+
+impl AccountClassInfo {
+    /// Decodes the whole constructor calldata of an account of this class
+    pub fn decode(&self, calldata: &[FieldElement]) -> Option<AccountConstructor> {
+        match (self.layout, calldata) {
+            (ConstructorLayout::PublicKey, [public_key]) => Some(AccountConstructor {
+                owner: Signer::Starknet(*public_key),
+                guardian: None,
+            }),
+            (ConstructorLayout::OwnerGuardian, [owner, guardian]) => Some(AccountConstructor {
+                owner: Signer::Starknet(*owner),
+                guardian: match *guardian == FieldElement::ZERO {
+                    true => None,
+                    false => Some(Signer::Starknet(*guardian)),
+                },
+            }),
+            (ConstructorLayout::Signers, _) => {
+                let (owner, rest) = Signer::decode(calldata)?;
+                // Option<Signer>: Some is variant 0, None is variant 1
+                let guardian = match rest.split_first()? {
+                    (variant, []) if *variant == FieldElement::ONE => None,
+                    (variant, signer) if *variant == FieldElement::ZERO => {
+                        match Signer::decode(signer)? {
+                            (guardian, []) => Some(guardian),
+                            _ => return None,
+                        }
+                    }
+                    _ => return None,
+                };
+                Some(AccountConstructor { owner, guardian })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Account contracts whose address can be derived from the public key,
+/// all of them being deployed with the public key as salt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccountClass {
+    OpenZeppelin = 0x00,
+    Argent = 0x01,
+    Braavos = 0x02,
+}
+
+impl TryFrom<u8> for AccountClass {
+    type Error = AppSW;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(AccountClass::OpenZeppelin),
+            0x01 => Ok(AccountClass::Argent),
+            0x02 => Ok(AccountClass::Braavos),
+            _ => Err(AppSW::BadP1P2),
+        }
+    }
+}
+
+impl AccountClass {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AccountClass::OpenZeppelin => "OpenZeppelin",
+            AccountClass::Argent => "Argent",
+            AccountClass::Braavos => "Braavos",
+        }
+    }
+
+    /// Class hash the account is deployed with
+    pub fn class_hash(&self) -> FieldElement {
+        match self {
+            AccountClass::OpenZeppelin => FieldElement::from(OPENZEPPELIN_0_8_1),
+            AccountClass::Argent => FieldElement::from(ARGENT_0_4_0),
+            AccountClass::Braavos => FieldElement::from(BRAAVOS_BASE),
+        }
+    }
+
+    /// Constructor calldata of an account owned by `public_key`
+    pub fn constructor_calldata(&self, public_key: &FieldElement) -> Vec<FieldElement> {
+        match self {
+            AccountClass::OpenZeppelin | AccountClass::Braavos => vec![*public_key],
+            // Starknet owner signer (Signer enum variant 0) and no guardian (Option::None)
+            AccountClass::Argent => vec![FieldElement::ZERO, *public_key, FieldElement::ONE],
+        }
+    }
+
+    /// Address of the account owned by `public_key`
+    pub fn address(&self, public_key: &FieldElement) -> FieldElement {
+        let calldata = self.constructor_calldata(public_key);
+        let mut hasher = PedersenHasher::default();
+        for d in calldata.iter() {
+            hasher.update(*d);
+        }
+        hasher.update(FieldElement::from(calldata.len()));
+        crypto::calculate_contract_address(
+            public_key,
+            &self.class_hash(),
+            &hasher.finalize(),
+            &FieldElement::ZERO,
+        )
+    }
+}
+
+/// Checks of a DEPLOY_ACCOUNT transaction shown on its review
+pub struct DeployAccountCheck {
+    /// The transaction deploys the contract address it is signed for
+    pub address_verified: bool,
+    /// The account is owned by the key of the signing path, None if it cannot be checked
+    /// (unknown account class or constructor calldata layout)
+    pub owner_verified: Option<bool>,
+}
+
+impl DeployAccountCheck {
+    /// `hasher_address` has absorbed the `nb_calldata` constructor calldata felts,
+    /// of which `calldata` are the first ones
+    pub fn new(
+        contract_address: &FieldElement,
+        class_hash: &FieldElement,
+        salt: &FieldElement,
+        mut hasher_address: PedersenHasher,
+        calldata: &[FieldElement],
+        nb_calldata: usize,
+        public_key: Option<&FieldElement>,
+    ) -> Self {
+        hasher_address.update(FieldElement::from(nb_calldata));
+        let address = crypto::calculate_contract_address(
+            salt,
+            class_hash,
+            &hasher_address.finalize(),
+            &FieldElement::ZERO,
+        );
+        let owner = match calldata.len() == nb_calldata {
+            true => get_account_class(class_hash)
+                .and_then(|class| class.decode(calldata))
+                .and_then(|constructor| constructor.owner_public_key()),
+            false => None,
+        };
+        Self {
+            address_verified: address == *contract_address,
+            owner_verified: owner.zip(public_key).map(|(owner, key)| owner == *key),
+        }
+    }
+}
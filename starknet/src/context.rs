@@ -1,10 +1,26 @@
 use crate::crypto;
+use crate::descriptor::CallDescriptors;
+use crate::erc20::ProvidedTokenInfo;
+use crate::fees::{FeeToken, Fees};
+use crate::typed_data::TypedData;
 use crate::types::FieldElement;
 
 extern crate alloc;
 use alloc::vec::Vec;
+use num_bigint::BigUint;
 
-#[derive(Default, Debug)]
+/// Maximum number of calls of an invoke transaction kept for clear signing
+pub const MAX_CALLS: usize = 8;
+/// Maximum number of calls of an invoke transaction summarised on a blind signing review
+pub const MAX_CALL_SUMMARIES: usize = 16;
+/// Maximum number of calldata felts of a call kept for clear signing
+pub const MAX_CALLDATA: usize = 16;
+/// Maximum number of paymaster_data felts of a V3 transaction
+pub const MAX_PAYMASTER_DATA: usize = 16;
+/// Maximum number of account_deployment_data felts of a V3 transaction
+pub const MAX_ACCOUNT_DEPLOYMENT_DATA: usize = 16;
+
+#[derive(Default, Debug, Clone)]
 pub struct Call {
     pub to: FieldElement,
     pub selector: FieldElement,
@@ -13,6 +29,14 @@ pub struct Call {
     pub calldata: Vec<FieldElement>,
 }
 
+/// Call shown on the review of a transaction which cannot be clear signed
+#[derive(Default, Debug, Clone, Copy)]
+pub struct CallSummary {
+    pub to: FieldElement,
+    pub selector: FieldElement,
+    pub nb_calldata: usize,
+}
+
 #[derive(Default, Debug)]
 pub struct InvokeTransactionV1 {
     pub version: FieldElement,
@@ -23,6 +47,10 @@ pub struct InvokeTransactionV1 {
     pub nb_calls: usize,
     pub nb_rcv_calls: usize,
     pub call: Call,
+    /// Received calls, up to MAX_CALLS
+    pub calls: Vec<Call>,
+    /// Summaries of the received calls, up to MAX_CALL_SUMMARIES
+    pub call_summaries: Vec<CallSummary>,
     pub hasher: crypto::pedersen::PedersenHasher,
     pub hasher_calldata: crypto::pedersen::PedersenHasher,
 }
@@ -31,10 +59,7 @@ pub struct InvokeTransactionV1 {
 pub struct InvokeTransactionV3 {
     pub version: FieldElement,
     pub sender_address: FieldElement,
-    pub tip: FieldElement,
-    pub l1_gas_bounds: FieldElement,
-    pub l2_gas_bounds: FieldElement,
-    pub l1_data_gas_bounds: FieldElement,
+    pub fees: Fees,
     pub paymaster_data: Vec<FieldElement>,
     pub chain_id: FieldElement,
     pub nonce: FieldElement,
@@ -43,6 +68,10 @@ pub struct InvokeTransactionV3 {
     pub nb_calls: usize,
     pub nb_rcv_calls: usize,
     pub call: Call,
+    /// Received calls, up to MAX_CALLS
+    pub calls: Vec<Call>,
+    /// Summaries of the received calls, up to MAX_CALL_SUMMARIES
+    pub call_summaries: Vec<CallSummary>,
     pub hasher: crypto::poseidon::PoseidonHasher,
     pub hasher_calldata: crypto::poseidon::PoseidonHasher,
 }
@@ -56,18 +85,22 @@ pub struct DeployAccountTransactionV1 {
     pub nonce: FieldElement,
     pub class_hash: FieldElement,
     pub contract_address_salt: FieldElement,
+    pub nb_constructor_calldata: usize,
+    pub nb_rcv_constructor_calldata: usize,
+    /// Received constructor calldata, up to MAX_CALLDATA
     pub constructor_calldata: Vec<FieldElement>,
     pub hasher: crypto::pedersen::PedersenHasher,
+    /// Hash of the contract address preimage (class_hash, salt, constructor_calldata)
+    pub hasher_calldata: crypto::pedersen::PedersenHasher,
+    /// Hash of the constructor calldata in the contract address
+    pub hasher_address: crypto::pedersen::PedersenHasher,
 }
 
 #[derive(Default, Debug)]
 pub struct DeployAccountTransactionV3 {
     pub version: FieldElement,
     pub contract_address: FieldElement,
-    pub tip: FieldElement,
-    pub l1_gas_bounds: FieldElement,
-    pub l2_gas_bounds: FieldElement,
-    pub l1_data_gas_bounds: FieldElement,
+    pub fees: Fees,
     pub paymaster_data: Vec<FieldElement>,
     pub chain_id: FieldElement,
     pub nonce: FieldElement,
@@ -76,8 +109,27 @@ pub struct DeployAccountTransactionV3 {
     pub contract_address_salt: FieldElement,
     pub nb_constructor_calldata: usize,
     pub nb_rcv_constructor_calldata: usize,
+    /// Received constructor calldata, up to MAX_CALLDATA
+    pub constructor_calldata: Vec<FieldElement>,
     pub hasher: crypto::poseidon::PoseidonHasher,
     pub hasher_calldata: crypto::poseidon::PoseidonHasher,
+    /// Hash of the constructor calldata in the contract address
+    pub hasher_address: crypto::pedersen::PedersenHasher,
+}
+
+#[derive(Default, Debug)]
+pub struct DeclareTransactionV3 {
+    pub version: FieldElement,
+    pub sender_address: FieldElement,
+    pub fees: Fees,
+    pub paymaster_data: Vec<FieldElement>,
+    pub chain_id: FieldElement,
+    pub nonce: FieldElement,
+    pub data_availability_mode: FieldElement,
+    pub account_deployment_data: Vec<FieldElement>,
+    pub class_hash: FieldElement,
+    pub compiled_class_hash: FieldElement,
+    pub hasher: crypto::poseidon::PoseidonHasher,
 }
 
 #[derive(Default, Debug)]
@@ -88,6 +140,7 @@ pub enum Transaction {
     InvokeV3(InvokeTransactionV3),
     DeployAccountV1(DeployAccountTransactionV1),
     DeployAccountV3(DeployAccountTransactionV3),
+    DeclareV3(DeclareTransactionV3),
 }
 
 impl Transaction {
@@ -97,6 +150,7 @@ impl Transaction {
             Transaction::InvokeV3(tx) => tx.nb_rcv_calls,
             Transaction::DeployAccountV1(_tx) => 1usize,
             Transaction::DeployAccountV3(_tx) => 1usize,
+            Transaction::DeclareV3(_tx) => 0usize,
             Transaction::None => 0usize,
         }
     }
@@ -107,20 +161,126 @@ impl Transaction {
             Transaction::InvokeV3(tx) => tx.nb_calls,
             Transaction::DeployAccountV1(_tx) => 1usize,
             Transaction::DeployAccountV3(_tx) => 1usize,
+            Transaction::DeclareV3(_tx) => 0usize,
             Transaction::None => 0usize,
         }
     }
+
+    /// Summaries of the received calls of an invoke transaction
+    pub fn get_call_summaries(&self) -> &[CallSummary] {
+        match self {
+            Transaction::InvokeV1(tx) => &tx.call_summaries,
+            Transaction::InvokeV3(tx) => &tx.call_summaries,
+            _ => &[],
+        }
+    }
+
+    /// paymaster_data of a V3 transaction, empty if not sponsored
+    pub fn get_paymaster_data(&self) -> &[FieldElement] {
+        match self {
+            Transaction::InvokeV3(tx) => &tx.paymaster_data,
+            Transaction::DeployAccountV3(tx) => &tx.paymaster_data,
+            Transaction::DeclareV3(tx) => &tx.paymaster_data,
+            _ => &[],
+        }
+    }
+
+    /// data_availability_mode of a V3 transaction, L1 for both nonce and fee otherwise
+    pub fn get_data_availability_mode(&self) -> FieldElement {
+        match self {
+            Transaction::InvokeV3(tx) => tx.data_availability_mode,
+            Transaction::DeployAccountV3(tx) => tx.data_availability_mode,
+            Transaction::DeclareV3(tx) => tx.data_availability_mode,
+            _ => FieldElement::ZERO,
+        }
+    }
+
+    /// account_deployment_data of a V3 transaction, empty if it deploys no account
+    pub fn get_account_deployment_data(&self) -> &[FieldElement] {
+        match self {
+            Transaction::InvokeV3(tx) => &tx.account_deployment_data,
+            Transaction::DeclareV3(tx) => &tx.account_deployment_data,
+            _ => &[],
+        }
+    }
+
+    pub fn get_version(&self) -> FieldElement {
+        match self {
+            Transaction::InvokeV1(tx) => tx.version,
+            Transaction::InvokeV3(tx) => tx.version,
+            Transaction::DeployAccountV1(tx) => tx.version,
+            Transaction::DeployAccountV3(tx) => tx.version,
+            Transaction::DeclareV3(tx) => tx.version,
+            Transaction::None => FieldElement::ZERO,
+        }
+    }
+
+    pub fn get_chain_id(&self) -> FieldElement {
+        match self {
+            Transaction::InvokeV1(tx) => tx.chain_id,
+            Transaction::InvokeV3(tx) => tx.chain_id,
+            Transaction::DeployAccountV1(tx) => tx.chain_id,
+            Transaction::DeployAccountV3(tx) => tx.chain_id,
+            Transaction::DeclareV3(tx) => tx.chain_id,
+            Transaction::None => FieldElement::ZERO,
+        }
+    }
+
+    /// Maximum fee the transaction can be charged, in the smallest unit of its fee token
+    pub fn get_max_fee(&self) -> (BigUint, FeeToken) {
+        let v1_max_fee = |max_fee: &FieldElement| BigUint::from_bytes_be(max_fee.value.as_ref());
+        match self {
+            Transaction::InvokeV1(tx) => (v1_max_fee(&tx.max_fee), FeeToken::Eth),
+            Transaction::InvokeV3(tx) => (tx.fees.max_fee(), FeeToken::Strk),
+            Transaction::DeployAccountV1(tx) => (v1_max_fee(&tx.max_fee), FeeToken::Eth),
+            Transaction::DeployAccountV3(tx) => (tx.fees.max_fee(), FeeToken::Strk),
+            Transaction::DeclareV3(tx) => (tx.fees.max_fee(), FeeToken::Strk),
+            Transaction::None => (BigUint::ZERO, FeeToken::Strk),
+        }
+    }
 }
 
+#[derive(PartialEq)]
 pub enum RequestType {
     Unknown,
     GetPubkey,
+    GetAccountAddress,
     #[cfg(feature = "signhash")]
     SignHash,
     SignTx,
     SignTxV1,
     SignDeployAccount,
     SignDeployAccountV1,
+    SignDeclare,
+    SignTypedData,
+}
+
+/// Progress of a transaction or typed data signing request,
+/// each step being identified by its p1
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TxState {
+    /// No transaction in progress, a request must start with its derivation path (p1 = 0)
+    #[default]
+    Idle,
+    /// Step fully received, the next one is expected
+    Done(u8),
+    /// Step partially received (streamed data or calls), it is expected again
+    Partial(u8),
+    /// Step received, any step from the first to the last one may follow
+    /// (typed data definitions, structs and values)
+    Between(u8, u8),
+}
+
+impl TxState {
+    /// Whether step p1 of the request in progress may be received in this state
+    pub fn expects(&self, p1: u8) -> bool {
+        match *self {
+            TxState::Idle => false,
+            TxState::Done(step) => p1 == step + 1,
+            TxState::Partial(step) => p1 == step,
+            TxState::Between(first, last) => (first..=last).contains(&p1),
+        }
+    }
 }
 
 #[derive(Default, Debug)]
@@ -136,6 +296,12 @@ use ledger_device_sdk::nbgl::{NbglHomeAndSettings, NbglSpinner};
 pub struct Ctx {
     pub req_type: RequestType,
     pub tx: Transaction,
+    pub tx_state: TxState,
+    pub typed_data: TypedData,
+    /// Token information provided by the host, kept across requests
+    pub tokens: Vec<ProvidedTokenInfo>,
+    /// Call descriptors provided by the host, kept across requests
+    pub descriptors: CallDescriptors,
     pub hash: FieldElement,
     pub signature: Signature,
     pub bip32_path: [u32; 6],
@@ -150,6 +316,10 @@ impl Ctx {
         Self {
             req_type: RequestType::Unknown,
             tx: Transaction::default(),
+            tx_state: TxState::default(),
+            typed_data: TypedData::default(),
+            tokens: Vec::new(),
+            descriptors: CallDescriptors::default(),
             hash: FieldElement::default(),
             signature: Signature::default(),
             bip32_path: [0u32; 6],
@@ -163,6 +333,8 @@ impl Ctx {
     pub fn reset(&mut self) {
         self.req_type = RequestType::Unknown;
         self.tx = Transaction::default();
+        self.tx_state = TxState::default();
+        self.typed_data = TypedData::default();
         self.hash = FieldElement::default();
         self.signature = Signature::default();
         self.bip32_path.fill(0);
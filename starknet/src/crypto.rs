@@ -1,10 +1,13 @@
 use ledger_device_sdk::ecc::{ECPublicKey, SeedDerive, Stark256};
-use ledger_device_sdk::io::{Reply, SyscallError};
+use ledger_device_sdk::hash::{sha2::Sha2_256, sha3::Keccak256, HashInit};
+use ledger_device_sdk::io::SyscallError;
+use ledger_secure_sdk_sys::{cx_ecdsa_verify_no_throw, cx_ecfp_public_key_t, CX_CURVE_SECP256K1};
 
 pub mod pedersen;
 pub mod poseidon;
 
 use crate::context::Ctx;
+use crate::status::AppSW;
 use crate::types::FieldElement;
 
 /// Length in bytes of an EIP-2645 derivation path (without m), e.g m/2645'/1195502025'/1148870696'/0'/0'/0
@@ -13,26 +16,102 @@ const EIP2645_PATH_BYTES_LENGTH: usize = 24;
 /// Hardened 2645 value
 const EIP2645_PATH_PREFIX: u32 = 0x80000A55;
 
-#[derive(Debug)]
-pub enum CryptoError {
-    UnvalidPathPrefix = 0xFF00,
-    UnvalidPathLength = 0xFF01,
-    Sign = 0xFF02,
+pub trait HasherTrait: Sized {
+    fn update(&mut self, data: FieldElement);
+    fn finalize(self) -> FieldElement;
+}
+
+/// Keccak-256 of the input truncated to 250 bits, as used for selectors and SNIP-12 type hashes
+pub fn starknet_keccak(data: &[u8]) -> FieldElement {
+    let mut res = FieldElement::default();
+    let mut keccak = Keccak256::new();
+    keccak.hash(data, &mut res.value).unwrap();
+    res.value[0] &= 0x03;
+    res
 }
 
-impl From<CryptoError> for Reply {
-    fn from(ce: CryptoError) -> Reply {
-        Reply(ce as u16)
+/// secp256k1 public key trusted to sign data provided by the host (e.g token information).
+/// Test builds (`trusted_test_key` feature) trust a key whose private part is in the test client.
+#[cfg(feature = "trusted_test_key")]
+const TRUSTED_PUBLIC_KEY: [u8; 65] = [
+    0x04, 0xbf, 0x8a, 0x66, 0xa9, 0x37, 0x5b, 0x94, 0xc8, 0x8c, 0x80, 0x4c, 0x18, 0xb7, 0x38, 0x27,
+    0xc3, 0x72, 0x90, 0x25, 0xb4, 0xf8, 0x81, 0x65, 0x19, 0x3f, 0xe5, 0xa4, 0x22, 0x03, 0xfe, 0x72,
+    0xa7, 0x8b, 0x70, 0x97, 0xaa, 0xbb, 0x95, 0x71, 0xb4, 0x11, 0x8d, 0x98, 0x19, 0xb2, 0xa1, 0x7c,
+    0x00, 0xd9, 0xcf, 0x3b, 0xb4, 0xe5, 0xcd, 0xd5, 0x4b, 0x49, 0xbe, 0x8b, 0xb6, 0x10, 0x75, 0x06,
+    0xd9,
+];
+/// Release builds trust the Ledger CAL key, provided hex encoded (uncompressed, 65 bytes)
+/// in the `STARKNET_TRUSTED_PUBLIC_KEY` environment variable at build time
+#[cfg(not(feature = "trusted_test_key"))]
+const TRUSTED_PUBLIC_KEY: [u8; 65] = match option_env!("STARKNET_TRUSTED_PUBLIC_KEY") {
+    Some(key) => parse_public_key(key),
+    None => {
+        panic!("STARKNET_TRUSTED_PUBLIC_KEY must be set, or the trusted_test_key feature enabled")
+    }
+};
+
+#[cfg(not(feature = "trusted_test_key"))]
+const fn parse_public_key(hex: &str) -> [u8; 65] {
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            b'A'..=b'F' => c - b'A' + 10,
+            _ => panic!("STARKNET_TRUSTED_PUBLIC_KEY is not hex encoded"),
+        }
+    }
+    let hex = hex.as_bytes();
+    let mut key = [0u8; 65];
+    if hex.len() != 2 * key.len() {
+        panic!("STARKNET_TRUSTED_PUBLIC_KEY is not a 65-byte public key");
+    }
+    let mut i = 0;
+    while i < key.len() {
+        key[i] = (nibble(hex[2 * i]) << 4) | nibble(hex[2 * i + 1]);
+        i += 1;
+    }
+    if key[0] != 0x04 {
+        panic!("STARKNET_TRUSTED_PUBLIC_KEY is not an uncompressed public key");
     }
+    key
 }
 
-pub trait HasherTrait: Sized {
-    fn update(&mut self, data: FieldElement);
-    fn finalize(self) -> FieldElement;
+/// Kind of data signed by the trusted key, prepended to the signed message
+/// so that a signature cannot be replayed for another kind of data
+#[derive(Debug, Clone, Copy)]
+pub enum SignedDataKind {
+    TokenInfo = 0x01,
+    CallDescriptor = 0x02,
+}
+
+/// Verifies a DER encoded ECDSA signature of the SHA-256 of `kind | message` with the trusted key
+pub fn verify_trusted_signature(kind: SignedDataKind, message: &[u8], signature: &[u8]) -> bool {
+    let mut hash = [0u8; 32];
+    let mut sha256 = Sha2_256::new();
+    if sha256.update(&[kind as u8]).is_err()
+        || sha256.update(message).is_err()
+        || sha256.finalize(&mut hash).is_err()
+    {
+        return false;
+    }
+    let key = cx_ecfp_public_key_t {
+        curve: CX_CURVE_SECP256K1,
+        W_len: TRUSTED_PUBLIC_KEY.len(),
+        W: TRUSTED_PUBLIC_KEY,
+    };
+    unsafe {
+        cx_ecdsa_verify_no_throw(
+            &key,
+            hash.as_ptr(),
+            hash.len(),
+            signature.as_ptr(),
+            signature.len(),
+        )
+    }
 }
 
 /// Helper function that signs with ECDSA in deterministic nonce
-pub fn sign_hash(ctx: &mut Ctx) -> Result<(), CryptoError> {
+pub fn sign_hash(ctx: &mut Ctx) -> Result<(), AppSW> {
     poseidon::poseidon_shift(&mut ctx.hash);
 
     match Stark256::derive_from_path(ctx.bip32_path.as_ref())
@@ -42,12 +121,12 @@ pub fn sign_hash(ctx: &mut Ctx) -> Result<(), CryptoError> {
             let der = s.0;
             match convert_der_to_rs(&der[..], &mut ctx.signature.r, &mut ctx.signature.s) {
                 Ok(_) => (),
-                Err(_err) => return Err(CryptoError::Sign),
+                Err(_err) => return Err(AppSW::SignFailed),
             }
             ctx.signature.v = s.2 as u8;
             Ok(())
         }
-        Err(_) => Err(CryptoError::Sign),
+        Err(_) => Err(AppSW::SignFailed),
     }
 }
 
@@ -61,13 +140,36 @@ pub fn get_pubkey(ctx: &Ctx) -> Result<ECPublicKey<65, 'W'>, SyscallError> {
     }
 }
 
+/// Address of a contract deployed by `deployer_address` (zero for DEPLOY_ACCOUNT transactions),
+/// `constructor_calldata_hash` being the Pedersen hash on elements of its constructor calldata
+pub fn calculate_contract_address(
+    salt: &FieldElement,
+    class_hash: &FieldElement,
+    constructor_calldata_hash: &FieldElement,
+    deployer_address: &FieldElement,
+) -> FieldElement {
+    let mut hasher = pedersen::PedersenHasher::default();
+    hasher.update(FieldElement::CONTRACT_ADDRESS_PREFIX);
+    hasher.update(*deployer_address);
+    hasher.update(*salt);
+    hasher.update(*class_hash);
+    hasher.update(*constructor_calldata_hash);
+    hasher.update(FieldElement::from(5u8));
+    let address = hasher.finalize();
+    // A Pedersen hash is lower than P < 2 * ADDR_BOUND
+    match address >= FieldElement::ADDR_BOUND {
+        true => address - FieldElement::ADDR_BOUND,
+        false => address,
+    }
+}
+
 fn read_be_u32(input: &mut &[u8]) -> u32 {
     let (int_bytes, rest) = input.split_at(core::mem::size_of::<u32>());
     *input = rest;
     u32::from_be_bytes(int_bytes.try_into().unwrap())
 }
 
-pub fn set_derivation_path(buf: &mut &[u8], ctx: &mut Ctx) -> Result<(), CryptoError> {
+pub fn set_derivation_path(buf: &mut &[u8], ctx: &mut Ctx) -> Result<(), AppSW> {
     match buf.len() {
         EIP2645_PATH_BYTES_LENGTH => {
             for i in 0..6 {
@@ -75,10 +177,10 @@ pub fn set_derivation_path(buf: &mut &[u8], ctx: &mut Ctx) -> Result<(), CryptoE
             }
             match ctx.bip32_path[0] {
                 EIP2645_PATH_PREFIX => Ok(()),
-                _ => Err(CryptoError::UnvalidPathPrefix),
+                _ => Err(AppSW::InvalidPathPrefix),
             }
         }
-        _ => Err(CryptoError::UnvalidPathLength),
+        _ => Err(AppSW::InvalidPathLength),
     }
 }
 
@@ -0,0 +1,182 @@
+use crate::{
+    context::Call,
+    crypto::{self, SignedDataKind},
+    status::AppSW,
+    types::FieldElement,
+};
+
+extern crate alloc;
+use alloc::{string::String, vec::Vec};
+
+/// Maximum number of call descriptors kept for the session
+pub const MAX_DESCRIPTORS: usize = 8;
+/// Maximum number of parameters of a call descriptor
+const MAX_PARAMS: usize = 8;
+/// Maximum length of the intent and of the parameter names
+const MAX_LABEL_LENGTH: usize = 32;
+/// Token reference designating the called contract itself
+const CALLED_CONTRACT: u8 = 0xFF;
+
+/// Cairo type of a call parameter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamType {
+    Felt,
+    ContractAddress,
+    U256,
+    /// u256 amount of the token whose address is the parameter at this index,
+    /// or the called contract if CALLED_CONTRACT
+    TokenAmount(u8),
+}
+
+impl ParamType {
+    /// Number of calldata felts of a parameter of this type
+    pub fn nb_felts(&self) -> usize {
+        match self {
+            ParamType::Felt | ParamType::ContractAddress => 1,
+            ParamType::U256 | ParamType::TokenAmount(_) => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub name: String,
+    pub kind: ParamType,
+}
+
+/// How to render the calls of a given entrypoint of a given contract
+#[derive(Debug, Clone, Default)]
+pub struct CallDescriptor {
+    pub address: FieldElement,
+    pub selector: FieldElement,
+    /// Human readable intent, e.g. "Deposit"
+    pub intent: String,
+    pub params: Vec<Param>,
+}
+
+impl CallDescriptor {
+    /// Calldata felt index of each parameter, None if the calldata does not match the descriptor
+    pub fn offsets(&self, call: &Call) -> Option<Vec<usize>> {
+        let mut offsets = Vec::with_capacity(self.params.len());
+        let mut offset = 0;
+        for param in self.params.iter() {
+            offsets.push(offset);
+            offset += param.kind.nb_felts();
+        }
+        match offset == call.nb_calldata && call.calldata.len() == call.nb_calldata {
+            true => Some(offsets),
+            false => None,
+        }
+    }
+
+    /// Address of the token of a TokenAmount parameter
+    pub fn token_address(&self, token_ref: u8, call: &Call, offsets: &[usize]) -> FieldElement {
+        match token_ref {
+            CALLED_CONTRACT => call.to,
+            idx => call.calldata[offsets[idx as usize]],
+        }
+    }
+}
+
+/// Call descriptors verified for the session, and the one being received
+#[derive(Debug, Default)]
+pub struct CallDescriptors {
+    pub list: Vec<CallDescriptor>,
+    pending: Option<CallDescriptor>,
+    /// Received payloads covered by the signature of the pending descriptor
+    signed: Vec<u8>,
+}
+
+impl CallDescriptors {
+    /// Descriptor of the function `selector` of contract `to`
+    pub fn get(&self, to: &FieldElement, selector: &FieldElement) -> Option<&CallDescriptor> {
+        self.list
+            .iter()
+            .find(|d| d.address == *to && d.selector == *selector)
+    }
+}
+
+/// Start a call descriptor:
+/// contract address (32) | selector (32) | intent length (1) | intent
+pub fn start_descriptor(data: &[u8], descriptors: &mut CallDescriptors) -> Result<(), AppSW> {
+    descriptors.pending = None;
+    descriptors.signed.clear();
+    if data.len() < 65 {
+        return Err(AppSW::DescriptorInvalidData);
+    }
+    let intent = read_label(&data[64..])?;
+    if data.len() != 65 + intent.len() {
+        return Err(AppSW::DescriptorInvalidData);
+    }
+    descriptors.pending = Some(CallDescriptor {
+        address: FieldElement::from(&data[0..32]),
+        selector: FieldElement::from(&data[32..64]),
+        intent,
+        params: Vec::new(),
+    });
+    descriptors.signed.extend_from_slice(data);
+    Ok(())
+}
+
+/// Add a parameter to the pending call descriptor:
+/// name length (1) | name | type (1: 0 felt, 1 ContractAddress, 2 u256, 3 token amount) |
+/// for token amounts, index of the ContractAddress parameter holding the token (1),
+/// 0xFF for the called contract
+pub fn add_param(data: &[u8], descriptors: &mut CallDescriptors) -> Result<(), AppSW> {
+    let pending = descriptors
+        .pending
+        .as_mut()
+        .ok_or(AppSW::DescriptorUnexpectedData)?;
+    if pending.params.len() == MAX_PARAMS {
+        return Err(AppSW::DescriptorInvalidData);
+    }
+    let name = read_label(data)?;
+    let kind = match &data[1 + name.len()..] {
+        [0] => ParamType::Felt,
+        [1] => ParamType::ContractAddress,
+        [2] => ParamType::U256,
+        [3, CALLED_CONTRACT] => ParamType::TokenAmount(CALLED_CONTRACT),
+        [3, idx] => match pending.params.get(*idx as usize) {
+            Some(p) if p.kind == ParamType::ContractAddress => ParamType::TokenAmount(*idx),
+            _ => return Err(AppSW::DescriptorInvalidData),
+        },
+        _ => return Err(AppSW::DescriptorInvalidData),
+    };
+    pending.params.push(Param { name, kind });
+    descriptors.signed.extend_from_slice(data);
+    Ok(())
+}
+
+/// Verify the signature of the pending call descriptor and keep it for the session.
+/// The signature covers the start and parameter payloads in order, prefixed with
+/// SignedDataKind::CallDescriptor.
+pub fn end_descriptor(signature: &[u8], descriptors: &mut CallDescriptors) -> Result<(), AppSW> {
+    let descriptor = descriptors
+        .pending
+        .take()
+        .ok_or(AppSW::DescriptorUnexpectedData)?;
+    let signed = core::mem::take(&mut descriptors.signed);
+    if !crypto::verify_trusted_signature(SignedDataKind::CallDescriptor, &signed, signature) {
+        return Err(AppSW::DescriptorInvalidSignature);
+    }
+
+    let list = &mut descriptors.list;
+    list.retain(|d| d.address != descriptor.address || d.selector != descriptor.selector);
+    if list.len() == MAX_DESCRIPTORS {
+        list.remove(0);
+    }
+    list.push(descriptor);
+    Ok(())
+}
+
+/// Length prefixed printable ASCII label
+fn read_label(data: &[u8]) -> Result<String, AppSW> {
+    let len = *data.first().ok_or(AppSW::DescriptorInvalidData)? as usize;
+    if len == 0 || len > MAX_LABEL_LENGTH || data.len() < 1 + len {
+        return Err(AppSW::DescriptorInvalidData);
+    }
+    match core::str::from_utf8(&data[1..1 + len]) {
+        Ok(s) if s.bytes().all(|c| c == b' ' || c.is_ascii_graphic()) => Ok(String::from(s)),
+        _ => Err(AppSW::DescriptorInvalidData),
+    }
+}
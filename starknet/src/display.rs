@@ -1,17 +1,30 @@
 extern crate alloc;
 use crate::{
+    account::{get_account_class, AccountClass, DeployAccountCheck, Signer},
     context::{
-        Call, DeployAccountTransactionV1, DeployAccountTransactionV3, InvokeTransactionV1,
-        InvokeTransactionV3,
+        Call, CallSummary, DeclareTransactionV3, DeployAccountTransactionV1,
+        DeployAccountTransactionV3, InvokeTransactionV1, InvokeTransactionV3,
     },
-    erc20::{ERC20_TOKENS, TRANSFER},
-    types::FieldElement,
+    crypto,
+    descriptor::{CallDescriptor, CallDescriptors, ParamType},
+    erc20::{get_token, Erc20Method, ProvidedTokenInfo, Token, ERC20_METHODS},
+    fees::{Fees, ResourceBounds},
+    status::AppSW,
+    transaction::{decode_da_modes, DaMode, TxVersion},
+    types::{FieldElement, U256},
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
 };
 
 use include_gif::include_gif;
 use ledger_device_sdk::io::Comm;
 #[cfg(any(target_os = "nanox", target_os = "nanosplus"))]
 use ledger_device_sdk::ui::gadgets::Validator;
+use num_bigint::BigUint;
 
 use crate::context::{Ctx, Transaction};
 
@@ -24,9 +37,13 @@ use ledger_device_sdk::ui::{
 };
 
 #[cfg(any(target_os = "nanox", target_os = "nanosplus"))]
-use crate::Ins;
+use crate::{
+    fees::{FeeToken, NB_FEE_CAP_LEVELS},
+    settings::{BLIND_SIGNING, ETH_FEE_CAP, STRK_FEE_CAP},
+    Ins,
+};
 
-use crate::settings::Settings;
+use crate::settings::{Settings, GROUPED_ADDRESSES, REJECT_OVER_FEE_CAP};
 #[cfg(any(target_os = "stax", target_os = "flex", target_os = "apex_p"))]
 use ledger_device_sdk::nbgl::{
     Field, NbglChoice, NbglGenericReview, NbglGlyph, NbglHomeAndSettings, NbglPageContent,
@@ -34,212 +51,504 @@ use ledger_device_sdk::nbgl::{
     TransactionType, TuneIndex,
 };
 
+/// Checks and warnings before the review of a transaction: supported version, unknown
+/// network and max fee over the cap set in the settings. Returns the status word of the
+/// rejected transaction.
+pub fn check_tx(ctx: &mut Ctx) -> Result<(), AppSW> {
+    TxVersion::try_from(ctx.tx.get_version())?;
+    let chain_id = ctx.tx.get_chain_id();
+    if known_network(&chain_id).is_none() && !unknown_network_ui(&network_to_string(&chain_id)) {
+        return Err(AppSW::UnknownChain);
+    }
+    if !fee_cap_ui(ctx) {
+        return Err(AppSW::FeeCapExceeded);
+    }
+    Ok(())
+}
+
 pub fn show_tx(ctx: &mut Ctx) -> Option<bool> {
+    // Key of the signing path, which should own a deployed account
+    let public_key = match ctx.tx {
+        Transaction::DeployAccountV1(_) | Transaction::DeployAccountV3(_) => {
+            crypto::get_pubkey(ctx)
+                .ok()
+                .map(|key| FieldElement::from(&key.as_ref()[1..33]))
+        }
+        _ => None,
+    };
+    let metadata = Metadata {
+        tokens: &ctx.tokens,
+        descriptors: &ctx.descriptors,
+    };
     let tx = &mut ctx.tx;
     match tx {
         Transaction::None => None,
-        Transaction::DeployAccountV3(tx) => show_tx_deploy_account_v3(tx),
-        Transaction::DeployAccountV1(tx) => show_tx_deploy_account_v1(tx),
-        Transaction::InvokeV3(tx) => show_tx_invoke_v3(tx),
-        Transaction::InvokeV1(tx) => show_tx_invoke_v1(tx),
+        Transaction::DeployAccountV3(tx) => show_tx_deploy_account_v3(tx, public_key.as_ref()),
+        Transaction::DeployAccountV1(tx) => show_tx_deploy_account_v1(tx, public_key.as_ref()),
+        Transaction::InvokeV3(tx) => show_tx_invoke_v3(tx, &metadata),
+        Transaction::InvokeV1(tx) => show_tx_invoke_v1(tx, &metadata),
+        Transaction::DeclareV3(tx) => show_tx_declare_v3(tx),
     }
 }
 
-fn show_tx_invoke_v3(tx: &InvokeTransactionV3) -> Option<bool> {
-    if tx.nb_calls > 1 {
+fn show_tx_invoke_v3(tx: &InvokeTransactionV3, metadata: &Metadata) -> Option<bool> {
+    if tx.calls.len() != tx.nb_rcv_calls {
         return None;
     }
-    match support_clear_sign(&tx.call) {
-        Some(idx) => {
-            let call = &tx.call;
-
-            let mut sender = tx.sender_address.to_hex_string();
-            sender.insert_str(0, "0x");
-            let token = ERC20_TOKENS[idx].ticker;
-            let mut to = call.calldata[0].to_hex_string();
-            to.insert_str(0, "0x");
-            let amount = call.calldata[1].to_dec_string(Some(ERC20_TOKENS[idx].decimals));
-
-            let max_fees = FieldElement::from(&tx.l1_gas_bounds.value[8..16])
-                * FieldElement::from(&tx.l1_gas_bounds.value[16..32])
-                + FieldElement::from(&tx.l2_gas_bounds.value[8..16])
-                    * FieldElement::from(&tx.l2_gas_bounds.value[16..32])
-                + FieldElement::from(&tx.l1_data_gas_bounds.value[8..16])
-                    * FieldElement::from(&tx.l1_data_gas_bounds.value[16..32]);
-
-            let mut max_fees_str = max_fees.to_dec_string(Some(18));
-            max_fees_str.push_str(" STRK");
-
-            let my_fields = [
-                Field {
-                    name: "From",
-                    value: sender.as_str(),
-                },
-                Field {
-                    name: "Token",
-                    value: token,
-                },
-                Field {
-                    name: "Amount",
-                    value: amount.as_str(),
-                },
-                Field {
-                    name: "To",
-                    value: to.as_str(),
-                },
-                Field {
-                    name: "Max Fees",
-                    value: max_fees_str.as_str(),
-                },
-            ];
+    let fees = fee_fields(&tx.fees);
+    let paymaster = paymaster_to_string(&tx.paymaster_data);
+    let mut header = vec![(String::from("Network"), network_to_string(&tx.chain_id))];
+    header.extend(da_mode_warning(&tx.data_availability_mode)?);
+    header.extend(decode_account_deployment(&tx.account_deployment_data)?);
+
+    show_tx_invoke(
+        &tx.sender_address,
+        tx.nb_calls,
+        &tx.calls,
+        metadata,
+        &fees,
+        paymaster.as_deref(),
+        &header,
+    )
+}
+
+fn show_tx_invoke_v1(tx: &InvokeTransactionV1, metadata: &Metadata) -> Option<bool> {
+    if tx.calls.len() != tx.nb_rcv_calls {
+        return None;
+    }
+    let mut max_fees_str = tx.max_fee.to_dec_string(Some(18));
+    max_fees_str.push_str(" ETH");
+
+    show_tx_invoke(
+        &tx.sender_address,
+        tx.nb_calls,
+        &tx.calls,
+        metadata,
+        &[("Max Fees", max_fees_str)],
+        None,
+        &[(String::from("Network"), network_to_string(&tx.chain_id))],
+    )
+}
+
+/// Review of an invoke transaction: a single call is detailed field by field,
+/// multicalls are shown as one summary per call.
+/// Returns None (blind signing) if any call cannot be decoded.
+fn show_tx_invoke(
+    sender_address: &FieldElement,
+    nb_calls: usize,
+    calls: &[Call],
+    metadata: &Metadata,
+    fees: &[(&str, String)],
+    paymaster: Option<&str>,
+    header: &[(String, String)],
+) -> Option<bool> {
+    if nb_calls == 0 || calls.len() != nb_calls {
+        return None;
+    }
+
+    let sender = address_to_string(sender_address);
+
+    if nb_calls == 1 {
+        let decoded = decode_call(&calls[0], metadata)?;
+        let mut my_fields: Vec<Field> = Vec::new();
+        my_fields.push(Field {
+            name: "From",
+            value: sender.as_str(),
+        });
+        for (name, value) in header.iter() {
+            my_fields.push(Field {
+                name: name.as_str(),
+                value: value.as_str(),
+            });
+        }
+        for (name, value) in decoded.fields.iter() {
+            my_fields.push(Field {
+                name,
+                value: value.as_str(),
+            });
+        }
+        for (name, value) in fees.iter() {
+            my_fields.push(Field {
+                name,
+                value: value.as_str(),
+            });
+        }
+        if let Some(paymaster) = paymaster {
+            my_fields.push(Field {
+                name: "Sponsored by",
+                value: paymaster,
+            });
+        }
+        return Some(show_tx_review(&my_fields));
+    }
+
+    let mut titles: Vec<String> = Vec::new();
+    let mut summaries: Vec<String> = Vec::new();
+    for (i, call) in calls.iter().enumerate() {
+        titles.push(format!("Call {}/{}", i + 1, nb_calls));
+        summaries.push(decode_call(call, metadata)?.summary);
+    }
+
+    let mut my_fields: Vec<Field> = Vec::new();
+    my_fields.push(Field {
+        name: "From",
+        value: sender.as_str(),
+    });
+    for (name, value) in header.iter() {
+        my_fields.push(Field {
+            name: name.as_str(),
+            value: value.as_str(),
+        });
+    }
+    for (title, summary) in titles.iter().zip(summaries.iter()) {
+        my_fields.push(Field {
+            name: title.as_str(),
+            value: summary.as_str(),
+        });
+    }
+    for (name, value) in fees.iter() {
+        my_fields.push(Field {
+            name,
+            value: value.as_str(),
+        });
+    }
+    if let Some(paymaster) = paymaster {
+        my_fields.push(Field {
+            name: "Sponsored by",
+            value: paymaster,
+        });
+    }
+    Some(show_tx_review(&my_fields))
+}
+
+/// Clear signing view of a call
+struct DecodedCall<'a> {
+    /// Fields reviewed when the transaction has a single call
+    fields: Vec<(&'a str, String)>,
+    /// One line description reviewed for each call of a multicall,
+    /// e.g. "Transfer 10 STRK to 0x..."
+    summary: String,
+}
+
+/// Host provided information used to decode calls
+struct Metadata<'a> {
+    tokens: &'a [ProvidedTokenInfo],
+    descriptors: &'a CallDescriptors,
+}
 
-            #[cfg(any(target_os = "nanox", target_os = "nanosplus"))]
-            {
-                let my_review = MultiFieldReview::new(
-                    &my_fields,
-                    &["Confirm Tx to sign"],
-                    Some(&EYE),
+/// Decode a call to a known ERC-20 token or matching a call descriptor,
+/// None if it cannot be decoded
+fn decode_call<'a>(call: &Call, metadata: &Metadata<'a>) -> Option<DecodedCall<'a>> {
+    if let Some(descriptor) = metadata.descriptors.get(&call.to, &call.selector) {
+        return decode_described_call(call, descriptor, metadata.tokens);
+    }
+    let (token, method) = support_clear_sign(call, metadata.tokens)?;
+    let data = &call.calldata;
+
+    match method {
+        Erc20Method::Transfer => {
+            let to = address_to_string(&data[0]);
+            let amount = token_amount(&data[1], data.get(2), token.decimals)?;
+            Some(DecodedCall {
+                summary: format!("Transfer {} {} to {}", amount, token.ticker, to),
+                fields: Vec::from([
+                    ("Token", token.ticker.to_string()),
+                    ("Amount", amount),
+                    ("To", to),
+                ]),
+            })
+        }
+        Erc20Method::Approve | Erc20Method::IncreaseAllowance | Erc20Method::DecreaseAllowance => {
+            let spender = address_to_string(&data[0]);
+            let amount = token_amount(&data[1], data.get(2), token.decimals)?;
+            let (action, summary) = match method {
+                Erc20Method::Approve => (
                     "Approve",
-                    Some(&VALIDATE_14),
-                    "Reject",
-                    Some(&CROSSMARK),
-                );
-                Some(my_review.show())
+                    format!("Approve {} {} for {}", amount, token.ticker, spender),
+                ),
+                Erc20Method::IncreaseAllowance => (
+                    "Increase allowance",
+                    format!(
+                        "Increase {} allowance of {} by {}",
+                        token.ticker, spender, amount
+                    ),
+                ),
+                _ => (
+                    "Decrease allowance",
+                    format!(
+                        "Decrease {} allowance of {} by {}",
+                        token.ticker, spender, amount
+                    ),
+                ),
+            };
+            Some(DecodedCall {
+                summary,
+                fields: Vec::from([
+                    ("Action", action.to_string()),
+                    ("Token", token.ticker.to_string()),
+                    ("Amount", amount),
+                    ("Spender", spender),
+                ]),
+            })
+        }
+        Erc20Method::TransferFrom => {
+            let owner = address_to_string(&data[0]);
+            let to = address_to_string(&data[1]);
+            let amount = token_amount(&data[2], data.get(3), token.decimals)?;
+            Some(DecodedCall {
+                summary: format!(
+                    "Transfer {} {} from {} to {}",
+                    amount, token.ticker, owner, to
+                ),
+                fields: Vec::from([
+                    ("Token", token.ticker.to_string()),
+                    ("Amount", amount),
+                    ("Owner", owner),
+                    ("To", to),
+                ]),
+            })
+        }
+    }
+}
+
+/// Decode a call with the parameter names and types of its descriptor
+fn decode_described_call<'a>(
+    call: &Call,
+    descriptor: &'a CallDescriptor,
+    tokens: &[ProvidedTokenInfo],
+) -> Option<DecodedCall<'a>> {
+    let offsets = descriptor.offsets(call)?;
+    let data = &call.calldata;
+
+    let mut fields: Vec<(&'a str, String)> = Vec::from([("Action", descriptor.intent.clone())]);
+    let mut summary = descriptor.intent.clone();
+    for (i, (param, offset)) in descriptor.params.iter().zip(offsets.iter()).enumerate() {
+        let value = match param.kind {
+            ParamType::Felt => data[*offset].to_dec_string(None),
+            ParamType::ContractAddress => address_to_string(&data[*offset]),
+            ParamType::U256 => {
+                U256::from_limbs(data[*offset], data[offset + 1])?.to_dec_string(None)
             }
-            #[cfg(any(target_os = "stax", target_os = "flex", target_os = "apex_p"))]
-            {
-                // Load glyph from file with include_gif macro. Creates an NBGL compatible glyph.
-                #[cfg(any(target_os = "stax", target_os = "flex"))]
-                const APP_ICON: NbglGlyph =
-                    NbglGlyph::from_include(include_gif!("starknet_64x64.gif", NBGL));
-                #[cfg(target_os = "apex_p")]
-                const APP_ICON: NbglGlyph =
-                    NbglGlyph::from_include(include_gif!("starknet_48x48.png", NBGL));
-
-                let review = NbglReview::new()
-                    .tx_type(TransactionType::Transaction)
-                    .titles("Review transaction", "", "Sign Transaction ?")
-                    .glyph(&APP_ICON);
-
-                Some(review.show(&my_fields))
+            ParamType::TokenAmount(token_ref) => {
+                let address = descriptor.token_address(token_ref, call, &offsets);
+                let token = get_token(&address, tokens)?;
+                let amount = token_amount(&data[*offset], data.get(offset + 1), token.decimals)?;
+                format!("{} {}", amount, token.ticker)
             }
-        }
-        None => None,
+        };
+        summary.push_str(match i {
+            0 => ": ",
+            _ => ", ",
+        });
+        summary.push_str(&format!("{} {}", param.name, value));
+        fields.push((param.name.as_str(), value));
     }
+    Some(DecodedCall { fields, summary })
 }
 
-fn show_tx_invoke_v1(tx: &InvokeTransactionV1) -> Option<bool> {
-    if tx.nb_calls > 1 {
-        return None;
+/// Name of the Starknet networks, None for any other chain id
+fn known_network(chain_id: &FieldElement) -> Option<&'static str> {
+    match *chain_id {
+        FieldElement::SN_MAIN => Some("Starknet Mainnet"),
+        FieldElement::SN_SEPOLIA => Some("Starknet Sepolia"),
+        _ => None,
     }
-    match support_clear_sign(&tx.call) {
-        Some(idx) => {
-            let call = &tx.call;
-
-            let mut sender = tx.sender_address.to_hex_string();
-            sender.insert_str(0, "0x");
-            let token = ERC20_TOKENS[idx].ticker;
-            let mut to = call.calldata[0].to_hex_string();
-            to.insert_str(0, "0x");
-            let amount = call.calldata[1].to_dec_string(Some(ERC20_TOKENS[idx].decimals));
-
-            let mut max_fees_str = tx.max_fee.to_dec_string(Some(18));
-            max_fees_str.push_str(" ETH");
-
-            let my_fields = [
-                Field {
-                    name: "From",
-                    value: sender.as_str(),
-                },
-                Field {
-                    name: "Token",
-                    value: token,
-                },
-                Field {
-                    name: "Amount",
-                    value: amount.as_str(),
-                },
-                Field {
-                    name: "To",
-                    value: to.as_str(),
-                },
-                Field {
-                    name: "Max Fees",
-                    value: max_fees_str.as_str(),
-                },
-            ];
+}
 
-            #[cfg(any(target_os = "nanox", target_os = "nanosplus"))]
-            {
-                let my_review = MultiFieldReview::new(
-                    &my_fields,
-                    &["Confirm Tx to sign"],
-                    Some(&EYE),
-                    "Approve",
-                    Some(&VALIDATE_14),
-                    "Reject",
+/// Network reviewed for a chain id: name of a Starknet network, chain id decoded as a
+/// short string for app-chains, or in hex if it is not printable
+fn network_to_string(chain_id: &FieldElement) -> String {
+    if let Some(name) = known_network(chain_id) {
+        return String::from(name);
+    }
+    let bytes = &chain_id.value[chain_id.value.iter().position(|b| *b != 0).unwrap_or(32)..];
+    match core::str::from_utf8(bytes) {
+        Ok(s) if !s.is_empty() && s.bytes().all(|c| c.is_ascii_graphic()) => String::from(s),
+        _ => felt_to_string(chain_id),
+    }
+}
+
+/// Warning shown before the review of a transaction for an unknown chain id,
+/// returns true if the user chooses to continue
+fn unknown_network_ui(network: &str) -> bool {
+    #[cfg(any(target_os = "nanox", target_os = "nanosplus"))]
+    {
+        let _ = network;
+        Validator::new("Unknown network").ask()
+    }
+    #[cfg(any(target_os = "stax", target_os = "flex", target_os = "apex_p"))]
+    {
+        NbglChoice::new().show(
+            "Unknown network",
+            &format!("This transaction is for chain {network}, not Starknet Mainnet or Sepolia"),
+            "Continue",
+            "Reject transaction",
+        )
+    }
+}
+
+/// Warn about or reject a transaction whose max fee is over the cap set in the settings,
+/// returns false if the transaction is rejected
+#[allow(unused_variables)]
+fn fee_cap_ui(ctx: &mut Ctx) -> bool {
+    let (max_fee, token) = ctx.tx.get_max_fee();
+    let Some(cap) = token.cap() else {
+        return true;
+    };
+    if max_fee <= BigUint::from(cap) {
+        return true;
+    }
+    let settings: Settings = Default::default();
+    let reject = settings.get_element(REJECT_OVER_FEE_CAP) != 0;
+    let max_fee = token.amount_str(max_fee);
+    let cap = token.amount_str(BigUint::from(cap));
+
+    #[cfg(any(target_os = "nanox", target_os = "nanosplus"))]
+    {
+        match reject {
+            true => {
+                let page = Page::new(
+                    PageStyle::PictureNormal,
+                    ["Max fee is over the", "cap set in Settings"],
                     Some(&CROSSMARK),
                 );
-                Some(my_review.show())
+                clear_screen();
+                page.place_and_wait();
+                false
             }
-            #[cfg(any(target_os = "stax", target_os = "flex", target_os = "apex_p"))]
-            {
-                // Load glyph from file with include_gif macro. Creates an NBGL compatible glyph.
-                #[cfg(any(target_os = "stax", target_os = "flex"))]
-                const APP_ICON: NbglGlyph =
-                    NbglGlyph::from_include(include_gif!("starknet_64x64.gif", NBGL));
-                #[cfg(target_os = "apex_p")]
-                const APP_ICON: NbglGlyph =
-                    NbglGlyph::from_include(include_gif!("starknet_48x48.png", NBGL));
-
-                let review = NbglReview::new()
-                    .tx_type(TransactionType::Transaction)
-                    .titles("Review transaction", "", "Sign Transaction ?")
-                    .glyph(&APP_ICON);
-
-                Some(review.show(&my_fields))
+            false => Validator::new("Max fee over cap").ask(),
+        }
+    }
+    #[cfg(any(target_os = "stax", target_os = "flex", target_os = "apex_p"))]
+    {
+        match reject {
+            true => {
+                let choice = NbglChoice::new().show(
+                    "Max fee over cap",
+                    &format!("Max fee {max_fee} is over the {cap} cap set in the settings"),
+                    "Go to settings",
+                    "Reject transaction",
+                );
+                if choice {
+                    ctx.home.set_start_page(PageIndex::Settings(0));
+                    ctx.home.show_and_return();
+                    ctx.home.set_start_page(PageIndex::Home);
+                }
+                false
             }
+            false => NbglChoice::new().show(
+                "Max fee over cap",
+                &format!("Max fee {max_fee} is over the {cap} cap set in the settings"),
+                "Continue",
+                "Reject transaction",
+            ),
         }
-        None => None,
     }
 }
 
-fn show_tx_deploy_account_v3(tx: &DeployAccountTransactionV3) -> Option<bool> {
-    let mut contract_address = tx.contract_address.to_hex_string();
-    contract_address.insert_str(0, "0x");
+/// Warning reviewed when the nonce or the fee of a V3 transaction does not use L1 data availability,
+/// None if the data availability modes are unknown
+fn da_mode_warning(data_availability_mode: &FieldElement) -> Option<Vec<(String, String)>> {
+    let modes = decode_da_modes(data_availability_mode).ok()?;
+    let mut fields = Vec::new();
+    if modes.nonce != DaMode::L1 || modes.fee != DaMode::L1 {
+        fields.push((
+            String::from("Warning"),
+            format!(
+                "Nonce DA {}, fee DA {}: L2 data availability is less secure than L1",
+                modes.nonce, modes.fee
+            ),
+        ));
+    }
+    Some(fields)
+}
 
-    let mut class_hash = tx.class_hash.to_hex_string();
-    class_hash.insert_str(0, "0x");
+/// Review of the account deployed along with a V3 transaction, decoded from its
+/// account_deployment_data: class hash | salt | constructor calldata length | constructor calldata.
+/// Empty if the transaction deploys no account, None if account_deployment_data is malformed.
+fn decode_account_deployment(data: &[FieldElement]) -> Option<Vec<(String, String)>> {
+    let mut fields = Vec::new();
+    if data.is_empty() {
+        return Some(fields);
+    }
+    if data.len() < 3 || data[2] != FieldElement::from(data.len() - 3) {
+        return None;
+    }
+    let (header, calldata) = data.split_at(3);
+    fields.push((
+        String::from("Deploy account"),
+        String::from("This transaction also deploys your account"),
+    ));
+    fields.push((String::from("Class Hash"), felt_to_string(&header[0])));
+    fields.push((String::from("Salt"), felt_to_string(&header[1])));
+    for (i, d) in calldata.iter().enumerate() {
+        fields.push((
+            format!("Constructor calldata {}/{}", i + 1, calldata.len()),
+            felt_to_string(d),
+        ));
+    }
+    Some(fields)
+}
 
-    let max_fees = FieldElement::from(&tx.l1_gas_bounds.value[8..16])
-        * FieldElement::from(&tx.l1_gas_bounds.value[16..32])
-        + FieldElement::from(&tx.l2_gas_bounds.value[8..16])
-            * FieldElement::from(&tx.l2_gas_bounds.value[16..32])
-        + FieldElement::from(&tx.l1_data_gas_bounds.value[8..16])
-            * FieldElement::from(&tx.l1_data_gas_bounds.value[16..32]);
+/// Max fee of a V3 transaction followed by its breakdown: tip and bounds of each resource
+fn fee_fields(fees: &Fees) -> Vec<(&'static str, String)> {
+    let bounds = |b: &ResourceBounds| format!("{} at {} FRI", b.max_amount, b.max_price_per_unit);
+    vec![
+        ("Max Fees", fees.max_fee_str()),
+        ("Tip", format!("{} FRI per L2 gas", fees.tip)),
+        ("L1 gas", bounds(&fees.l1_gas)),
+        ("L2 gas", bounds(&fees.l2_gas)),
+        ("L1 data gas", bounds(&fees.l1_data_gas)),
+    ]
+}
 
-    let mut fees = max_fees.to_dec_string(Some(18));
-    fees.push_str(" STRK");
+/// Paymaster of a sponsored V3 transaction, the first felt of its paymaster_data
+fn paymaster_to_string(paymaster_data: &[FieldElement]) -> Option<String> {
+    paymaster_data.first().map(address_to_string)
+}
 
-    let my_fields = [
-        Field {
-            name: "Deploy account",
-            value: contract_address.as_str(),
-        },
-        Field {
-            name: "Max Fees",
-            value: fees.as_str(),
-        },
-        Field {
-            name: "Class Hash",
-            value: class_hash.as_str(),
-        },
-    ];
+fn felt_to_string(felt: &FieldElement) -> String {
+    let mut s = felt.to_hex_string();
+    s.insert_str(0, "0x");
+    s
+}
 
+/// Checksummed address, split in blocks of 4 characters if set in the settings
+fn address_to_string(address: &FieldElement) -> String {
+    let checksummed = address.to_checksum_string();
+    let settings: Settings = Default::default();
+    if settings.get_element(GROUPED_ADDRESSES) == 0 {
+        return checksummed;
+    }
+    let mut s = String::from("0x");
+    for (i, c) in checksummed[2..].chars().enumerate() {
+        if i != 0 && i % 4 == 0 {
+            s.push(' ');
+        }
+        s.push(c);
+    }
+    s
+}
+
+/// Token amount from the low and high limbs of a u256, "Unlimited" for the u256 max value.
+/// The high limb is omitted by legacy transfers. Returns None for malformed limbs.
+fn token_amount(
+    low: &FieldElement,
+    high: Option<&FieldElement>,
+    decimals: usize,
+) -> Option<String> {
+    let amount = U256::from_limbs(*low, high.copied().unwrap_or_default())?;
+    match amount.is_max() {
+        true => Some(String::from("Unlimited")),
+        false => Some(amount.to_dec_string(Some(decimals))),
+    }
+}
+
+fn show_tx_review(fields: &[Field]) -> bool {
     #[cfg(any(target_os = "nanox", target_os = "nanosplus"))]
     {
         let my_review = MultiFieldReview::new(
-            &my_fields,
+            fields,
             &["Confirm Tx to sign"],
             Some(&EYE),
             "Approve",
@@ -247,7 +556,7 @@ fn show_tx_deploy_account_v3(tx: &DeployAccountTransactionV3) -> Option<bool> {
             "Reject",
             Some(&CROSSMARK),
         );
-        Some(my_review.show())
+        my_review.show()
     }
     #[cfg(any(target_os = "stax", target_os = "flex", target_os = "apex_p"))]
     {
@@ -264,41 +573,281 @@ fn show_tx_deploy_account_v3(tx: &DeployAccountTransactionV3) -> Option<bool> {
             .titles("Review transaction", "", "Sign Transaction ?")
             .glyph(&APP_ICON);
 
-        Some(review.show(&my_fields))
+        review.show(fields)
+    }
+}
+
+fn show_tx_declare_v3(tx: &DeclareTransactionV3) -> Option<bool> {
+    let sender = address_to_string(&tx.sender_address);
+    let class_hash = felt_to_string(&tx.class_hash);
+    let compiled_class_hash = felt_to_string(&tx.compiled_class_hash);
+
+    let fees = fee_fields(&tx.fees);
+
+    let mut header = da_mode_warning(&tx.data_availability_mode)?;
+    header.extend(decode_account_deployment(&tx.account_deployment_data)?);
+
+    let network = network_to_string(&tx.chain_id);
+
+    let mut my_fields = vec![
+        Field {
+            name: "From",
+            value: sender.as_str(),
+        },
+        Field {
+            name: "Network",
+            value: network.as_str(),
+        },
+    ];
+    for (name, value) in header.iter() {
+        my_fields.push(Field {
+            name: name.as_str(),
+            value: value.as_str(),
+        });
+    }
+    my_fields.push(Field {
+        name: "Declare class",
+        value: class_hash.as_str(),
+    });
+    my_fields.push(Field {
+        name: "Compiled Class Hash",
+        value: compiled_class_hash.as_str(),
+    });
+    for (name, value) in fees.iter() {
+        my_fields.push(Field {
+            name,
+            value: value.as_str(),
+        });
+    }
+    let paymaster = paymaster_to_string(&tx.paymaster_data);
+    if let Some(paymaster) = paymaster.as_deref() {
+        my_fields.push(Field {
+            name: "Sponsored by",
+            value: paymaster,
+        });
+    }
+    Some(show_tx_review(&my_fields))
+}
+
+fn show_tx_deploy_account_v3(
+    tx: &DeployAccountTransactionV3,
+    public_key: Option<&FieldElement>,
+) -> Option<bool> {
+    let contract_address = address_to_string(&tx.contract_address);
+    let check = DeployAccountCheck::new(
+        &tx.contract_address,
+        &tx.class_hash,
+        &tx.contract_address_salt,
+        tx.hasher_address,
+        &tx.constructor_calldata,
+        tx.nb_constructor_calldata,
+        public_key,
+    );
+
+    let class = account_class_fields(
+        &tx.class_hash,
+        &tx.constructor_calldata,
+        tx.nb_constructor_calldata,
+    );
+
+    let fees = fee_fields(&tx.fees);
+
+    let header = da_mode_warning(&tx.data_availability_mode)?;
+
+    let network = network_to_string(&tx.chain_id);
+
+    let mut my_fields = vec![Field {
+        name: "Deploy account",
+        value: contract_address.as_str(),
+    }];
+    for (name, value) in deploy_account_check_fields(&check) {
+        my_fields.push(Field { name, value });
+    }
+    my_fields.push(Field {
+        name: "Network",
+        value: network.as_str(),
+    });
+    for (name, value) in header.iter() {
+        my_fields.push(Field {
+            name: name.as_str(),
+            value: value.as_str(),
+        });
+    }
+    for (name, value) in class.iter() {
+        my_fields.push(Field {
+            name,
+            value: value.as_str(),
+        });
+    }
+    for (name, value) in fees.iter() {
+        my_fields.push(Field {
+            name,
+            value: value.as_str(),
+        });
     }
+    let paymaster = paymaster_to_string(&tx.paymaster_data);
+    if let Some(paymaster) = paymaster.as_deref() {
+        my_fields.push(Field {
+            name: "Sponsored by",
+            value: paymaster,
+        });
+    }
+    Some(show_tx_review(&my_fields))
 }
 
-fn show_tx_deploy_account_v1(tx: &DeployAccountTransactionV1) -> Option<bool> {
-    // display contract_address, fees and class_hash
-    let mut contract_address = tx.contract_address.to_hex_string();
-    contract_address.insert_str(0, "0x");
+fn show_tx_deploy_account_v1(
+    tx: &DeployAccountTransactionV1,
+    public_key: Option<&FieldElement>,
+) -> Option<bool> {
+    // display contract_address, its checks, fees and class_hash
+    let contract_address = address_to_string(&tx.contract_address);
+    let check = DeployAccountCheck::new(
+        &tx.contract_address,
+        &tx.class_hash,
+        &tx.contract_address_salt,
+        tx.hasher_address,
+        &tx.constructor_calldata,
+        tx.nb_constructor_calldata,
+        public_key,
+    );
 
-    let mut class_hash = tx.class_hash.to_hex_string();
-    class_hash.insert_str(0, "0x");
+    let class = account_class_fields(
+        &tx.class_hash,
+        &tx.constructor_calldata,
+        tx.nb_constructor_calldata,
+    );
 
     let mut fees = tx.max_fee.to_dec_string(Some(18));
     fees.push_str(" ETH");
 
-    let my_fields = [
+    let network = network_to_string(&tx.chain_id);
+
+    let mut my_fields = vec![Field {
+        name: "Deploy account",
+        value: contract_address.as_str(),
+    }];
+    for (name, value) in deploy_account_check_fields(&check) {
+        my_fields.push(Field { name, value });
+    }
+    my_fields.extend([
         Field {
-            name: "Deploy account",
-            value: contract_address.as_str(),
+            name: "Network",
+            value: network.as_str(),
         },
         Field {
             name: "Max Fees",
             value: fees.as_str(),
         },
-        Field {
-            name: "Class Hash",
-            value: class_hash.as_str(),
-        },
-    ];
+    ]);
+    for (name, value) in class.iter() {
+        my_fields.push(Field {
+            name,
+            value: value.as_str(),
+        });
+    }
+    Some(show_tx_review(&my_fields))
+}
+
+/// Review fields of the class of a deployed account: its name and decoded constructor
+/// calldata if the class is known, its hash otherwise
+fn account_class_fields(
+    class_hash: &FieldElement,
+    calldata: &[FieldElement],
+    nb_calldata: usize,
+) -> Vec<(&'static str, String)> {
+    let Some(class) = get_account_class(class_hash) else {
+        return vec![("Class Hash", felt_to_string(class_hash))];
+    };
+    let mut fields = vec![("Account class", String::from(class.name))];
+    let constructor = match calldata.len() == nb_calldata {
+        true => class.decode(calldata),
+        false => None,
+    };
+    let Some(constructor) = constructor else {
+        return fields;
+    };
+    if constructor.owner_public_key().is_none() {
+        fields.push(("Signer type", String::from(constructor.owner.kind())));
+    }
+    let owner_label = match constructor.owner {
+        Signer::Starknet(_) | Signer::Secp256r1(_) => "Owner public key",
+        Signer::Secp256k1(_) => "Owner key hash",
+        Signer::Eip191(_) => "Owner address",
+    };
+    fields.push((owner_label, signer_to_string(&constructor.owner)));
+    let guardian = match constructor.guardian {
+        None => String::from("None"),
+        Some(guardian @ Signer::Starknet(_)) => signer_to_string(&guardian),
+        Some(guardian) => format!("{} {}", guardian.kind(), signer_to_string(&guardian)),
+    };
+    fields.push(("Guardian", guardian));
+    fields
+}
+
+fn signer_to_string(signer: &Signer) -> String {
+    match signer {
+        Signer::Starknet(key) | Signer::Secp256k1(key) => felt_to_string(key),
+        Signer::Secp256r1(key) => {
+            let mut s = String::from("0x");
+            s.push_str(&hex::encode(key.to_be_bytes()));
+            s
+        }
+        Signer::Eip191(address) => {
+            // Ethereum addresses are 20 bytes long
+            let mut s = String::from("0x");
+            s.push_str(&hex::encode(&address.value[12..]));
+            s
+        }
+    }
+}
+
+/// Review fields of the checks of a deployed account: its address and its owner
+fn deploy_account_check_fields(check: &DeployAccountCheck) -> [(&'static str, &'static str); 2] {
+    let address = match check.address_verified {
+        true => "Verified",
+        false => "Does not match",
+    };
+    let owner = match check.owner_verified {
+        Some(true) => "This device",
+        Some(false) => "Not this device",
+        None => "Unverified",
+    };
+    [("Address check", address), ("Owner key", owner)]
+}
+
+pub fn show_typed_data(ctx: &mut Ctx) -> Option<bool> {
+    let td = &ctx.typed_data;
+    let fields = td.fields.as_ref()?;
+
+    let mut my_fields: Vec<Field> = Vec::new();
+    for (label, value) in td.domain_fields.iter() {
+        let name = match label.as_str() {
+            "name" => "Domain",
+            "version" => "Version",
+            "chainId" => "Chain ID",
+            _ => continue,
+        };
+        my_fields.push(Field {
+            name,
+            value: value.as_str(),
+        });
+    }
+    my_fields.push(Field {
+        name: "SNIP-12 revision",
+        value: td.revision.as_str(),
+    });
+    for (label, value) in fields.iter() {
+        my_fields.push(Field {
+            name: label.as_str(),
+            value: value.as_str(),
+        });
+    }
 
     #[cfg(any(target_os = "nanox", target_os = "nanosplus"))]
     {
         let my_review = MultiFieldReview::new(
             &my_fields,
-            &["Confirm Tx to sign"],
+            &["Confirm Message to sign"],
             Some(&EYE),
             "Approve",
             Some(&VALIDATE_14),
@@ -318,25 +867,68 @@ fn show_tx_deploy_account_v1(tx: &DeployAccountTransactionV1) -> Option<bool> {
             NbglGlyph::from_include(include_gif!("starknet_48x48.png", NBGL));
 
         let review = NbglReview::new()
-            .tx_type(TransactionType::Transaction)
-            .titles("Review transaction", "", "Sign Transaction ?")
+            .tx_type(TransactionType::Message)
+            .titles("Review message", "", "Sign message ?")
             .glyph(&APP_ICON);
 
         Some(review.show(&my_fields))
     }
 }
 
+/// Blind signing review of a hash. The hash of a transaction is preceded by its network,
+/// the contract, function and calldata length of each of its calls and its paymaster.
 pub fn show_hash(ctx: &mut Ctx, is_tx_hash: bool) -> bool {
     let mut hash = ctx.hash.to_hex_string();
     hash.make_ascii_uppercase();
 
-    let my_field = [Field {
+    let mut header: Vec<(String, String)> = Vec::new();
+    if is_tx_hash {
+        header.push((
+            String::from("Network"),
+            network_to_string(&ctx.tx.get_chain_id()),
+        ));
+        // Unknown modes are rejected when the transaction fields are received
+        header.extend(da_mode_warning(&ctx.tx.get_data_availability_mode()).unwrap_or_default());
+        // Account deployment data which cannot be decoded is still reported
+        header.extend(
+            decode_account_deployment(ctx.tx.get_account_deployment_data()).unwrap_or_else(|| {
+                vec![(
+                    String::from("Deploy account"),
+                    String::from("This transaction also deploys an account"),
+                )]
+            }),
+        );
+        header.extend(call_summary_fields(
+            ctx.tx.get_nb_calls(),
+            ctx.tx.get_call_summaries(),
+            &ctx.descriptors,
+        ));
+    }
+    let paymaster = match is_tx_hash {
+        true => paymaster_to_string(ctx.tx.get_paymaster_data()),
+        false => None,
+    };
+
+    let mut my_field: Vec<Field> = Vec::new();
+    for (name, value) in header.iter() {
+        my_field.push(Field {
+            name: name.as_str(),
+            value: value.as_str(),
+        });
+    }
+    if let Some(paymaster) = paymaster.as_deref() {
+        my_field.push(Field {
+            name: "Sponsored by",
+            value: paymaster,
+        });
+    }
+    my_field.push(Field {
         name: match is_tx_hash {
             true => "Transaction Hash",
             false => "Hash",
         },
         value: hash.as_str(),
-    }];
+    });
 
     #[cfg(any(target_os = "nanox", target_os = "nanosplus"))]
     {
@@ -385,6 +977,56 @@ pub fn show_hash(ctx: &mut Ctx, is_tx_hash: bool) -> bool {
     }
 }
 
+/// Fields summarising the calls of a transaction which cannot be clear signed,
+/// the calls beyond MAX_CALL_SUMMARIES being only counted
+fn call_summary_fields(
+    nb_calls: usize,
+    summaries: &[CallSummary],
+    descriptors: &CallDescriptors,
+) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for (i, summary) in summaries.iter().enumerate() {
+        fields.push((
+            format!("Call {}/{}", i + 1, nb_calls),
+            address_to_string(&summary.to),
+        ));
+        fields.push((
+            String::from("Function"),
+            function_name(&summary.to, &summary.selector, descriptors),
+        ));
+        fields.push((
+            String::from("Calldata length"),
+            summary.nb_calldata.to_string(),
+        ));
+    }
+    if nb_calls > summaries.len() {
+        fields.push((
+            String::from("Other calls"),
+            format!("{} not shown", nb_calls - summaries.len()),
+        ));
+    }
+    fields
+}
+
+/// Intent of the call descriptor or name of the ERC-20 function called,
+/// the selector if the function is unknown
+fn function_name(
+    to: &FieldElement,
+    selector: &FieldElement,
+    descriptors: &CallDescriptors,
+) -> String {
+    if let Some(descriptor) = descriptors.get(to, selector) {
+        return descriptor.intent.clone();
+    }
+    match ERC20_METHODS
+        .iter()
+        .find(|(s, _)| *selector == FieldElement::from(*s))
+    {
+        Some((_, method)) => method.name().to_string(),
+        None => felt_to_string(selector),
+    }
+}
+
 pub fn show_step(text: &str, ctx: &mut Ctx) {
     #[cfg(any(target_os = "nanosplus", target_os = "nanox"))]
     {
@@ -480,6 +1122,60 @@ pub fn pkey_ui(key: &[u8], ctx: &mut Ctx) -> bool {
     }
 }
 
+#[allow(unused_variables)]
+pub fn account_address_ui(class: AccountClass, address: &FieldElement, ctx: &mut Ctx) -> bool {
+    let address = address_to_string(address);
+
+    let my_fields = [
+        Field {
+            name: "Account",
+            value: class.name(),
+        },
+        Field {
+            name: "Address",
+            value: address.as_str(),
+        },
+    ];
+
+    #[cfg(any(target_os = "nanox", target_os = "nanosplus"))]
+    {
+        let my_review = MultiFieldReview::new(
+            &my_fields,
+            &["Confirm Address"],
+            Some(&EYE),
+            "Approve",
+            Some(&VALIDATE_14),
+            "Reject",
+            Some(&CROSSMARK),
+        );
+
+        my_review.show()
+    }
+    #[cfg(any(target_os = "stax", target_os = "flex", target_os = "apex_p"))]
+    {
+        let tvl = TagValueList::new(&my_fields, 4, false, true);
+        let tvc = TagValueConfirm::new(&tvl, TuneIndex::LookAtMe, "Approve", "");
+
+        match NbglGenericReview::new()
+            .add_content(NbglPageContent::TagValueConfirm(tvc))
+            .show("Reject")
+        {
+            true => {
+                let status = NbglStatus::new();
+                status.text("Address Confirmed").show(true);
+                ctx.home.show_and_return();
+                true
+            }
+            false => {
+                let status = NbglStatus::new();
+                status.text("Address Rejected").show(false);
+                ctx.home.show_and_return();
+                false
+            }
+        }
+    }
+}
+
 #[cfg(any(target_os = "nanox", target_os = "nanosplus"))]
 use ledger_device_sdk::io::Event;
 
@@ -504,12 +1200,26 @@ fn about_ui(comm: &mut Comm) -> Event<Ins> {
 fn settings_ui(comm: &mut Comm) -> Event<Ins> {
     {
         let settings: Settings = Default::default();
-        let mut bs_enabled: bool = settings.get_element(0) != 0;
+        let mut bs_enabled: bool = settings.get_element(BLIND_SIGNING) != 0;
         let mut bs_status = if bs_enabled { "Enabled" } else { "Disabled" };
 
         loop {
+            let strk_cap = FeeToken::Strk.cap_str(settings.get_element(STRK_FEE_CAP));
+            let eth_cap = FeeToken::Eth.cap_str(settings.get_element(ETH_FEE_CAP));
+            let over_cap = match settings.get_element(REJECT_OVER_FEE_CAP) {
+                0 => "Warn",
+                _ => "Reject",
+            };
+            let address_format = match settings.get_element(GROUPED_ADDRESSES) {
+                0 => "Full",
+                _ => "Grouped",
+            };
             let pages = [
                 &Page::from((["Blind Signing", bs_status], true)),
+                &Page::from((["STRK fee cap", strk_cap.as_str()], true)),
+                &Page::from((["ETH fee cap", eth_cap.as_str()], true)),
+                &Page::from((["Over fee cap", over_cap], true)),
+                &Page::from((["Address format", address_format], true)),
                 &Page::from(("Back", &BACK)),
             ];
             match MultiPageMenu::new(comm, &pages).show() {
@@ -518,16 +1228,33 @@ fn settings_ui(comm: &mut Comm) -> Event<Ins> {
                     bs_enabled = !bs_enabled;
                     match bs_enabled {
                         true => {
-                            settings.set_element(0, 1);
+                            settings.set_element(BLIND_SIGNING, 1);
                             bs_status = "Enabled";
                         }
                         false => {
-                            settings.set_element(0, 0);
+                            settings.set_element(BLIND_SIGNING, 0);
                             bs_status = "Disabled";
                         }
                     }
                 }
-                EventOrPageIndex::Index(1) => return main_ui(comm),
+                EventOrPageIndex::Index(i @ (1 | 2)) => {
+                    let token = if i == 1 {
+                        FeeToken::Strk
+                    } else {
+                        FeeToken::Eth
+                    };
+                    let level = settings.get_element(token.setting());
+                    settings.set_element(token.setting(), (level + 1) % NB_FEE_CAP_LEVELS);
+                }
+                EventOrPageIndex::Index(3) => {
+                    let reject = settings.get_element(REJECT_OVER_FEE_CAP);
+                    settings.set_element(REJECT_OVER_FEE_CAP, (reject == 0) as u8);
+                }
+                EventOrPageIndex::Index(4) => {
+                    let grouped = settings.get_element(GROUPED_ADDRESSES);
+                    settings.set_element(GROUPED_ADDRESSES, (grouped == 0) as u8);
+                }
+                EventOrPageIndex::Index(5) => return main_ui(comm),
                 EventOrPageIndex::Index(_) => (),
             }
         }
@@ -565,7 +1292,27 @@ pub fn main_ui_nbgl(_comm: &mut Comm) -> NbglHomeAndSettings {
     #[cfg(target_os = "apex_p")]
     const APP_ICON: NbglGlyph = NbglGlyph::from_include(include_gif!("starknet_48x48.png", NBGL));
 
-    let settings_strings = [["Blind signing", "Enable transaction blind signing"]];
+    // Switches are stored in the settings in this order, a STRK or ETH fee cap switch
+    // selecting the first cap level
+    let settings_strings = [
+        ["Blind signing", "Enable transaction blind signing"],
+        [
+            "STRK fee cap",
+            "Cap the max fee of STRK transactions at 10 STRK (100 STRK cap on Nano only)",
+        ],
+        [
+            "ETH fee cap",
+            "Cap the max fee of ETH transactions at 0.01 ETH (0.1 ETH cap on Nano only)",
+        ],
+        [
+            "Reject over fee cap",
+            "Reject transactions over the fee cap instead of warning",
+        ],
+        [
+            "Grouped addresses",
+            "Show addresses in blocks of 4 characters",
+        ],
+    ];
     let mut settings: Settings = Default::default();
 
     // Display the home screen.
@@ -610,12 +1357,23 @@ pub fn blind_signing_enable_ui(ctx: &mut Ctx) {
     }
 }
 
-fn support_clear_sign(call: &Call) -> Option<usize> {
-    for (idx, t) in ERC20_TOKENS.iter().enumerate() {
-        if call.to == FieldElement::from(t.address) && call.selector == FieldElement::from(TRANSFER)
-        {
-            return Some(idx);
-        }
+/// Known ERC-20 token and method of a call, the token being either
+/// in the static list or provided by the host
+fn support_clear_sign<'a>(
+    call: &Call,
+    tokens: &'a [ProvidedTokenInfo],
+) -> Option<(Token<'a>, Erc20Method)> {
+    let (_, method) = ERC20_METHODS
+        .iter()
+        .find(|(selector, _)| call.selector == FieldElement::from(*selector))?;
+    // Legacy transfers may omit the high part of the amount
+    let nb_calldata = match method {
+        Erc20Method::Transfer => 2..=3,
+        Erc20Method::TransferFrom => 4..=4,
+        _ => 3..=3,
+    };
+    if !nb_calldata.contains(&call.nb_calldata) || call.calldata.len() != call.nb_calldata {
+        return None;
     }
-    None
+    get_token(&call.to, tokens).map(|token| (token, *method))
 }
@@ -1,3 +1,10 @@
+use crate::crypto::{self, SignedDataKind};
+use crate::status::AppSW;
+use crate::types::FieldElement;
+
+extern crate alloc;
+use alloc::{string::String, vec::Vec};
+
 #[derive(Debug)]
 pub struct TokenInfo {
     pub address: &'static str,
@@ -26,4 +33,133 @@ pub const ERC20_TOKENS: [TokenInfo; NB_ERC20_TOKENS] = [
 ];
 
 pub const TRANSFER: &str = "0083afd3f4caedc6eebf44246fe54e38c95e3179a5ec9ea81740eca5b482d12e";
-//pub const APPROVE: &str = "0219209e083275171774dab1df80982e9df2096516f06319c5c6d71ae0a8480c";
+pub const APPROVE: &str = "0219209e083275171774dab1df80982e9df2096516f06319c5c6d71ae0a8480c";
+pub const INCREASE_ALLOWANCE: &str =
+    "01d13ab0a76d7407b1d5faccd4b3d8a9efe42f3d3c21766431d4fafb30f45bd4";
+pub const INCREASE_ALLOWANCE_CAMEL: &str =
+    "016cc063b8338363cf388ce7fe1df408bf10f16cd51635d392e21d852fafb683";
+pub const DECREASE_ALLOWANCE: &str =
+    "03b076186c19fe96221e4dfacd40c519f612eae02e0555e4e115a2a6cf2f1c1f";
+pub const DECREASE_ALLOWANCE_CAMEL: &str =
+    "01aaf3e6107dd1349c81543ff4221a326814f77dadcc5810807b74f1a49ded4e";
+pub const TRANSFER_FROM: &str = "03704ffe8fba161be0e994951751a5033b1462b918ff785c0a636be718dfdb68";
+pub const TRANSFER_FROM_CAMEL: &str =
+    "0041b033f4a31df8067c24d1e9b550a2ce75fd4a29e1147af9752174f0e6cb20";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Erc20Method {
+    /// transfer(recipient, amount)
+    Transfer,
+    /// approve(spender, amount)
+    Approve,
+    /// increase_allowance(spender, added_value)
+    IncreaseAllowance,
+    /// decrease_allowance(spender, subtracted_value)
+    DecreaseAllowance,
+    /// transfer_from(sender, recipient, amount)
+    TransferFrom,
+}
+
+impl Erc20Method {
+    /// Function name, the camelCase variants sharing the name of their snake_case method
+    pub fn name(&self) -> &'static str {
+        match self {
+            Erc20Method::Transfer => "transfer",
+            Erc20Method::Approve => "approve",
+            Erc20Method::IncreaseAllowance => "increase_allowance",
+            Erc20Method::DecreaseAllowance => "decrease_allowance",
+            Erc20Method::TransferFrom => "transfer_from",
+        }
+    }
+}
+
+pub const NB_ERC20_METHODS: usize = 8;
+
+pub const ERC20_METHODS: [(&str, Erc20Method); NB_ERC20_METHODS] = [
+    (TRANSFER, Erc20Method::Transfer),
+    (APPROVE, Erc20Method::Approve),
+    (INCREASE_ALLOWANCE, Erc20Method::IncreaseAllowance),
+    (INCREASE_ALLOWANCE_CAMEL, Erc20Method::IncreaseAllowance),
+    (DECREASE_ALLOWANCE, Erc20Method::DecreaseAllowance),
+    (DECREASE_ALLOWANCE_CAMEL, Erc20Method::DecreaseAllowance),
+    (TRANSFER_FROM, Erc20Method::TransferFrom),
+    (TRANSFER_FROM_CAMEL, Erc20Method::TransferFrom),
+];
+
+/// Maximum number of host provided tokens kept for the session
+pub const MAX_PROVIDED_TOKENS: usize = 8;
+/// Maximum length of a host provided ticker
+const MAX_TICKER_LENGTH: usize = 16;
+
+/// Token information provided by the host, signed by the trusted key
+#[derive(Debug, Clone)]
+pub struct ProvidedTokenInfo {
+    pub address: FieldElement,
+    pub ticker: String,
+    pub decimals: usize,
+}
+
+/// Ticker and decimals of a token, either from ERC20_TOKENS or provided by the host
+#[derive(Debug)]
+pub struct Token<'a> {
+    pub ticker: &'a str,
+    pub decimals: usize,
+}
+
+/// Looks up a token in ERC20_TOKENS first, then in the tokens provided by the host
+pub fn get_token<'a>(
+    address: &FieldElement,
+    provided: &'a [ProvidedTokenInfo],
+) -> Option<Token<'a>> {
+    if let Some(t) = ERC20_TOKENS
+        .iter()
+        .find(|t| *address == FieldElement::from(t.address))
+    {
+        return Some(Token {
+            ticker: t.ticker,
+            decimals: t.decimals,
+        });
+    }
+    provided
+        .iter()
+        .find(|t| t.address == *address)
+        .map(|t| Token {
+            ticker: t.ticker.as_str(),
+            decimals: t.decimals,
+        })
+}
+
+/// Verifies token information sent by the host and caches it for the session.
+/// Payload: address (32) | decimals (1) | ticker length (1) | ticker | DER signature,
+/// the signature covering everything before it, prefixed with SignedDataKind::TokenInfo.
+pub fn provide_token_info(data: &[u8], provided: &mut Vec<ProvidedTokenInfo>) -> Result<(), AppSW> {
+    if data.len() < 34 {
+        return Err(AppSW::TokenInfoInvalidData);
+    }
+    let ticker_len = data[33] as usize;
+    let signed_len = 34 + ticker_len;
+    if ticker_len == 0 || ticker_len > MAX_TICKER_LENGTH || data.len() <= signed_len {
+        return Err(AppSW::TokenInfoInvalidData);
+    }
+    let ticker = match core::str::from_utf8(&data[34..signed_len]) {
+        Ok(t) if t.bytes().all(|c| c.is_ascii_graphic()) => t,
+        _ => return Err(AppSW::TokenInfoInvalidData),
+    };
+
+    let (message, signature) = data.split_at(signed_len);
+    if !crypto::verify_trusted_signature(SignedDataKind::TokenInfo, message, signature) {
+        return Err(AppSW::TokenInfoInvalidSignature);
+    }
+
+    let token = ProvidedTokenInfo {
+        address: FieldElement::from(&data[0..32]),
+        ticker: String::from(ticker),
+        decimals: data[32] as usize,
+    };
+    provided.retain(|t| t.address != token.address);
+    if provided.len() == MAX_PROVIDED_TOKENS {
+        provided.remove(0);
+    }
+    provided.push(token);
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use crate::settings::{Settings, ETH_FEE_CAP, STRK_FEE_CAP};
+use crate::status::AppSW;
+use crate::types::to_dec_string;
+
+extern crate alloc;
+use alloc::string::String;
+use num_bigint::BigUint;
+
+const FIELD_ELEMENT_SIZE: usize = 32;
+
+/// Resource names as encoded in the first 8 bytes of the resource bounds felts
+const L1_GAS: &[u8; 8] = b"\0\0L1_GAS";
+const L2_GAS: &[u8; 8] = b"\0\0L2_GAS";
+const L1_DATA_GAS: &[u8; 8] = b"\0L1_DATA";
+
+/// Fee caps selectable in the settings, in FRI for STRK and in WEI for ETH,
+/// indexed by the cap level stored in the settings, level 0 meaning no cap
+pub const NB_FEE_CAP_LEVELS: u8 = 3;
+const STRK_FEE_CAPS: [u128; NB_FEE_CAP_LEVELS as usize] =
+    [0, 10 * 10u128.pow(18), 100 * 10u128.pow(18)];
+const ETH_FEE_CAPS: [u128; NB_FEE_CAP_LEVELS as usize] = [0, 10u128.pow(16), 10u128.pow(17)];
+
+/// Bounds of a resource: resource name (8) | max amount (8) | max price per unit (16)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ResourceBounds {
+    pub max_amount: u64,
+    pub max_price_per_unit: u128,
+}
+
+impl ResourceBounds {
+    fn parse(data: &[u8], name: &[u8; 8]) -> Result<Self, AppSW> {
+        if data.len() != FIELD_ELEMENT_SIZE || data[0..8] != name[..] {
+            return Err(AppSW::InvalidResourceBounds);
+        }
+        Ok(ResourceBounds {
+            max_amount: u64::from_be_bytes(data[8..16].try_into().unwrap()),
+            max_price_per_unit: u128::from_be_bytes(data[16..32].try_into().unwrap()),
+        })
+    }
+
+    /// max_amount * max_price_per_unit
+    fn max_cost(&self) -> BigUint {
+        BigUint::from(self.max_amount) * BigUint::from(self.max_price_per_unit)
+    }
+}
+
+/// Fee fields of a V3 transaction
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Fees {
+    pub tip: u64,
+    pub l1_gas: ResourceBounds,
+    pub l2_gas: ResourceBounds,
+    /// Zero when the transaction has no L1 data gas bounds
+    pub l1_data_gas: ResourceBounds,
+}
+
+impl Fees {
+    /// Parse the fees APDU payload: tip (32) | L1_GAS bounds (32) | L2_GAS bounds (32) |
+    /// optional L1_DATA bounds (32)
+    pub fn parse(data: &[u8]) -> Result<Self, AppSW> {
+        if data.len() != 3 * FIELD_ELEMENT_SIZE && data.len() != 4 * FIELD_ELEMENT_SIZE {
+            return Err(AppSW::InvalidResourceBounds);
+        }
+        let mut iter = data.chunks(FIELD_ELEMENT_SIZE);
+        let tip = iter.next().unwrap();
+        if tip[0..24].iter().any(|b| *b != 0) {
+            return Err(AppSW::InvalidTip);
+        }
+        Ok(Fees {
+            tip: u64::from_be_bytes(tip[24..32].try_into().unwrap()),
+            l1_gas: ResourceBounds::parse(iter.next().unwrap(), L1_GAS)?,
+            l2_gas: ResourceBounds::parse(iter.next().unwrap(), L2_GAS)?,
+            l1_data_gas: match iter.next() {
+                Some(d) => ResourceBounds::parse(d, L1_DATA_GAS)?,
+                None => ResourceBounds::default(),
+            },
+        })
+    }
+
+    /// Maximum fee the transaction can be charged, in FRI:
+    /// sum of max_amount * max_price_per_unit of each resource, plus tip * L2 gas max_amount
+    pub fn max_fee(&self) -> BigUint {
+        self.l1_gas.max_cost()
+            + self.l2_gas.max_cost()
+            + self.l1_data_gas.max_cost()
+            + BigUint::from(self.tip) * BigUint::from(self.l2_gas.max_amount)
+    }
+
+    /// Max fee in STRK, the fee token of V3 transactions
+    pub fn max_fee_str(&self) -> String {
+        FeeToken::Strk.amount_str(self.max_fee())
+    }
+}
+
+/// Token the fees of a transaction are paid in: STRK for V3 transactions, ETH for V1 ones
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeeToken {
+    Strk,
+    Eth,
+}
+
+impl FeeToken {
+    pub fn ticker(&self) -> &'static str {
+        match self {
+            FeeToken::Strk => "STRK",
+            FeeToken::Eth => "ETH",
+        }
+    }
+
+    /// Index of the cap level of the token in the settings
+    pub fn setting(&self) -> usize {
+        match self {
+            FeeToken::Strk => STRK_FEE_CAP,
+            FeeToken::Eth => ETH_FEE_CAP,
+        }
+    }
+
+    /// Fee cap of a given level, None for no cap
+    pub fn cap_at(&self, level: u8) -> Option<u128> {
+        let caps = match self {
+            FeeToken::Strk => &STRK_FEE_CAPS,
+            FeeToken::Eth => &ETH_FEE_CAPS,
+        };
+        caps.get(level as usize).copied().filter(|cap| *cap != 0)
+    }
+
+    /// Fee cap currently set in the settings, None if fees are not capped
+    pub fn cap(&self) -> Option<u128> {
+        let settings: Settings = Default::default();
+        self.cap_at(settings.get_element(self.setting()))
+    }
+
+    /// Amount of the token, from its smallest unit (FRI or WEI)
+    pub fn amount_str(&self, amount: BigUint) -> String {
+        let mut s = to_dec_string(amount, Some(18));
+        s.push(' ');
+        s.push_str(self.ticker());
+        s
+    }
+
+    /// Fee cap of a given level as displayed in the settings
+    #[allow(unused)]
+    pub fn cap_str(&self, level: u8) -> String {
+        match self.cap_at(level) {
+            Some(cap) => self.amount_str(BigUint::from(cap)),
+            None => String::from("Off"),
+        }
+    }
+}
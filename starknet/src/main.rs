@@ -1,27 +1,34 @@
 #![no_std]
 #![no_main]
 
+mod account;
 mod context;
 mod crypto;
+mod descriptor;
 mod display;
 mod erc20;
+mod fees;
 mod settings;
+mod status;
 mod transaction;
+mod typed_data;
 mod types;
 
 extern crate alloc;
 use alloc::{format, vec::Vec};
 
 use context::{
-    Ctx, DeployAccountTransactionV1, DeployAccountTransactionV3, InvokeTransactionV1,
-    InvokeTransactionV3, RequestType, Transaction,
+    Ctx, DeclareTransactionV3, DeployAccountTransactionV1, DeployAccountTransactionV3,
+    InvokeTransactionV1, InvokeTransactionV3, RequestType, Transaction, TxState,
 };
 use ledger_device_sdk::io;
 #[cfg(any(target_os = "stax", target_os = "flex", target_os = "apex_p"))]
 use ledger_device_sdk::uxapp;
+#[cfg(feature = "poseidon")]
 use types::FieldElement;
 
 use settings::Settings;
+use status::AppSW;
 
 ledger_device_sdk::set_panic!(ledger_device_sdk::exiting_panic);
 
@@ -32,6 +39,8 @@ const PARSING_STEP_TX_WORDING: &str = "Parsing transaction...";
 
 const PARSING_STEP_CALL_WORDING: &str = "Parsing call ";
 
+const PARSING_STEP_MESSAGE_WORDING: &str = "Parsing message...";
+
 #[no_mangle]
 extern "C" fn sample_main() {
     // Init comm and set the expected CLA byte for the application
@@ -80,29 +89,83 @@ enum Ins {
     SignTxV1,
     SignDeployAccount,
     SignDeployAccountV1,
+    SignDeclare,
     #[cfg(feature = "poseidon")]
     Poseidon,
+    SignTypedData,
+    ProvideTokenInfo,
+    ProvideCallDescriptor,
+    GetAccountAddress {
+        display: bool,
+        class: account::AccountClass,
+    },
+}
+
+impl Ins {
+    /// Request type of a transaction or typed data signing instruction
+    fn tx_request_type(&self) -> Option<RequestType> {
+        match self {
+            Ins::SignTx => Some(RequestType::SignTx),
+            Ins::SignTxV1 => Some(RequestType::SignTxV1),
+            Ins::SignDeployAccount => Some(RequestType::SignDeployAccount),
+            Ins::SignDeployAccountV1 => Some(RequestType::SignDeployAccountV1),
+            Ins::SignDeclare => Some(RequestType::SignDeclare),
+            Ins::SignTypedData => Some(RequestType::SignTypedData),
+            _ => None,
+        }
+    }
+
+    /// State of a signing request once its step p1 is received, streamed data, calls
+    /// and typed data definitions, structs and values being received over several APDUs
+    fn tx_state_after(&self, p1: u8, p2: u8) -> TxState {
+        match (self, p1) {
+            (Ins::SignTx, 3 | 4) | (Ins::SignDeployAccount, 3) | (Ins::SignDeclare, 3 | 4)
+                if p2 == transaction::DataChunk::More.into() =>
+            {
+                TxState::Partial(p1)
+            }
+            (Ins::SignTx, 6)
+            | (Ins::SignTxV1, 3)
+            | (Ins::SignDeployAccount, 5)
+            | (Ins::SignDeployAccountV1, 3) => TxState::Partial(p1),
+            (Ins::SignTypedData, 2) => TxState::Between(2, 3),
+            (Ins::SignTypedData, 3 | 4) => TxState::Between(3, 4),
+            _ => TxState::Done(p1),
+        }
+    }
 }
 
 impl TryFrom<io::ApduHeader> for Ins {
-    type Error = io::StatusWords;
+    type Error = AppSW;
     fn try_from(header: io::ApduHeader) -> Result<Self, Self::Error> {
         match (header.ins, header.p1, header.p2) {
             (0, 0, 0) => Ok(Ins::GetVersion),
-            (0, _, _) => Err(io::StatusWords::BadP1P2),
+            (0, _, _) => Err(AppSW::BadP1P2),
             (1, 0 | 1, 0) => Ok(Ins::GetPubkey {
                 display: header.p1 != 0,
             }),
-            (1, _, _) => Err(io::StatusWords::BadP1P2),
+            (1, _, _) => Err(AppSW::BadP1P2),
             #[cfg(feature = "signhash")]
             (2, _, _) => Ok(Ins::SignHash),
+            (3 | 5 | 11, 3, 2..) | (3 | 11, 4, 2..) => Err(AppSW::BadP1P2),
             (3, _, _) => Ok(Ins::SignTx),
             (4, _, _) => Ok(Ins::SignTxV1),
             (5, _, _) => Ok(Ins::SignDeployAccount),
             (6, _, _) => Ok(Ins::SignDeployAccountV1),
             #[cfg(feature = "poseidon")]
             (7, _, _) => Ok(Ins::Poseidon),
-            (_, _, _) => Err(io::StatusWords::BadIns),
+            (8, _, _) => Ok(Ins::SignTypedData),
+            (9, 0, 0) => Ok(Ins::ProvideTokenInfo),
+            (9, _, _) => Err(AppSW::BadP1P2),
+            (10, 0..=2, 0) => Ok(Ins::ProvideCallDescriptor),
+            (10, _, _) => Err(AppSW::BadP1P2),
+            (11, _, _) => Ok(Ins::SignDeclare),
+            (12, 0 | 1, _) => Ok(Ins::GetAccountAddress {
+                display: header.p1 != 0,
+                class: account::AccountClass::try_from(header.p2)?,
+            }),
+            (12, _, _) => Err(AppSW::BadP1P2),
+            (_, _, _) => Err(AppSW::BadIns),
         }
     }
 }
@@ -123,6 +186,62 @@ fn send_data(comm: &mut io::Comm, data: Result<Option<Vec<u8>>, Reply>) {
     }
 }
 
+/// Signs the hash of the request if approved and replies with the signature,
+/// preceded by the hash for transactions and typed data
+fn sign_and_reply(ctx: &mut Ctx, comm: &mut io::Comm, approved: bool) {
+    let is_tx = is_tx_request(ctx);
+    if !approved {
+        display::show_status(false, is_tx, ctx);
+        send_data(comm, Err(AppSW::Deny.into()));
+        return;
+    }
+    let mut rdata: Vec<u8> = Vec::new();
+    #[cfg(feature = "signhash")]
+    let with_hash = ctx.req_type != RequestType::SignHash;
+    #[cfg(not(feature = "signhash"))]
+    let with_hash = true;
+    if with_hash {
+        rdata.extend_from_slice(ctx.hash.value.as_ref());
+    }
+    crypto::sign_hash(ctx).unwrap();
+    rdata.extend_from_slice([SIG_LENGTH].as_slice());
+    rdata.extend_from_slice(ctx.signature.r.as_ref());
+    rdata.extend_from_slice(ctx.signature.s.as_ref());
+    rdata.extend_from_slice([ctx.signature.v].as_slice());
+    display::show_status(true, is_tx, ctx);
+    send_data(comm, Ok(Some(rdata)));
+}
+
+/// Reviews the hash of a request which cannot be clear signed, if blind signing is enabled
+fn blind_sign_and_reply(ctx: &mut Ctx, comm: &mut io::Comm) {
+    let settings: Settings = Default::default();
+    if settings.get_element(settings::BLIND_SIGNING) == 0 {
+        display::blind_signing_enable_ui(ctx);
+        send_data(comm, Err(AppSW::BlindSigningDisabled.into()));
+        return;
+    }
+    let is_tx = is_tx_request(ctx);
+    // Delay lock to prevent the device to pinlock
+    #[cfg(any(target_os = "stax", target_os = "flex", target_os = "apex_p"))]
+    if is_tx {
+        uxapp::UxEvent::DelayLock.request();
+    }
+    let approved = display::show_hash(ctx, is_tx);
+    sign_and_reply(ctx, comm, approved);
+}
+
+/// Whether the request signs a transaction, rather than a hash or a typed data
+fn is_tx_request(ctx: &Ctx) -> bool {
+    matches!(
+        ctx.req_type,
+        RequestType::SignTx
+            | RequestType::SignTxV1
+            | RequestType::SignDeployAccount
+            | RequestType::SignDeployAccountV1
+            | RequestType::SignDeclare
+    )
+}
+
 fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
     if comm.rx == 0 {
         send_data(comm, Err(io::StatusWords::NothingReceived.into()));
@@ -141,6 +260,19 @@ fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
 
     let mut rdata: Vec<u8> = Vec::new();
 
+    // Steps of a transaction or typed data signing request must be received in order. The
+    // request is aborted on an unexpected step or an error, its state being set back on success.
+    let mut next_tx_state = TxState::Idle;
+    if let Some(req_type) = ins.tx_request_type() {
+        let expected = p1 == 0 || (ctx.req_type == req_type && ctx.tx_state.expects(p1));
+        ctx.tx_state = TxState::Idle;
+        if !expected {
+            send_data(comm, Err(AppSW::WrongState.into()));
+            return;
+        }
+        next_tx_state = ins.tx_state_after(p1, p2);
+    }
+
     match ins {
         Ins::GetVersion => {
             let version_major = env!("CARGO_PKG_VERSION_MAJOR").parse::<u8>().unwrap();
@@ -175,7 +307,7 @@ fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
                                 rdata.extend_from_slice(key.as_ref());
                                 send_data(comm, Ok(Some(rdata)));
                             } else {
-                                send_data(comm, Err(io::StatusWords::UserCancelled.into()));
+                                send_data(comm, Err(AppSW::Deny.into()));
                             }
                         }
                     }
@@ -198,28 +330,8 @@ fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
                 }
             }
             _ => {
-                let settings: Settings = Default::default();
-                if settings.get_element(0) == 0 {
-                    display::blind_signing_enable_ui(ctx);
-                    send_data(comm, Err(io::StatusWords::UserCancelled.into()));
-                } else {
-                    ctx.hash = data.into();
-                    match display::show_hash(ctx, false) {
-                        true => {
-                            crypto::sign_hash(ctx).unwrap();
-                            rdata.extend_from_slice([0x41].as_slice());
-                            rdata.extend_from_slice(ctx.signature.r.as_ref());
-                            rdata.extend_from_slice(ctx.signature.s.as_ref());
-                            rdata.extend_from_slice([ctx.signature.v].as_slice());
-                            display::show_status(true, false, ctx);
-                            send_data(comm, Ok(Some(rdata)));
-                        }
-                        false => {
-                            display::show_status(false, false, ctx);
-                            send_data(comm, Err(io::StatusWords::UserCancelled.into()));
-                        }
-                    }
-                }
+                ctx.hash = data.into();
+                blind_sign_and_reply(ctx, comm);
             }
         },
         Ins::SignTx => match p1 {
@@ -229,6 +341,7 @@ fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
                 ctx.tx = Transaction::InvokeV3(InvokeTransactionV3::default());
                 match crypto::set_derivation_path(&mut data, ctx) {
                     Ok(()) => {
+                        ctx.tx_state = next_tx_state;
                         send_data(comm, Ok(None));
                     }
                     Err(e) => {
@@ -238,29 +351,63 @@ fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
             }
             1 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                transaction::set_tx_fields(data, &mut ctx.tx);
-                send_data(comm, Ok(None));
+                match transaction::set_tx_fields(data, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
             }
             2 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                transaction::set_tx_fees(data, &mut ctx.tx);
-                send_data(comm, Ok(None));
+                match transaction::set_tx_fees(data, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
             }
             3 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                transaction::set_paymaster_data(data, p2, &mut ctx.tx);
-                send_data(comm, Ok(None));
+                match transaction::set_paymaster_data(data, p2, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
             }
             4 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                transaction::set_account_deployment_data(data, p2, &mut ctx.tx);
-                send_data(comm, Ok(None));
+                match transaction::set_account_deployment_data(data, p2, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
             }
             5 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                let nb_calls = FieldElement::from(data);
-                transaction::set_calldata_nb(&mut ctx.tx, nb_calls);
-                send_data(comm, Ok(None));
+                match transaction::set_calldata_nb(data, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
             }
             6 => {
                 let nb_rcv_calls = match p2 == transaction::SetCallStep::New.into() {
@@ -280,72 +427,31 @@ fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
                 // Delay lock to prevent the device to pinlock
                 #[cfg(any(target_os = "stax", target_os = "flex", target_os = "apex_p"))]
                 uxapp::UxEvent::DelayLock.request();
-                if let Some(err) = transaction::set_calldata(data, p2.into(), &mut ctx.tx).err() {
-                    send_data(comm, Err(Reply(err as u16)));
+                if let Err(e) = transaction::set_calldata(data, p2, &mut ctx.tx) {
+                    send_data(comm, Err(e.into()));
+                    return;
                 }
                 match transaction::tx_complete(&mut ctx.tx) {
                     None => {
+                        ctx.tx_state = next_tx_state;
                         send_data(comm, Ok(None));
                     }
                     Some(hash) => {
                         ctx.hash = hash;
+                        if let Err(e) = display::check_tx(ctx) {
+                            display::show_status(false, true, ctx);
+                            send_data(comm, Err(e.into()));
+                            return;
+                        }
                         match display::show_tx(ctx) {
-                            Some(approved) => match approved {
-                                true => {
-                                    rdata.extend_from_slice(ctx.hash.value.as_ref());
-                                    crypto::sign_hash(ctx).unwrap();
-                                    rdata.extend_from_slice([SIG_LENGTH].as_slice());
-                                    rdata.extend_from_slice(ctx.signature.r.as_ref());
-                                    rdata.extend_from_slice(ctx.signature.s.as_ref());
-                                    rdata.extend_from_slice([ctx.signature.v].as_slice());
-                                    display::show_status(true, true, ctx);
-                                    send_data(comm, Ok(Some(rdata)));
-                                }
-                                false => {
-                                    display::show_status(false, true, ctx);
-                                    send_data(comm, Err(io::StatusWords::UserCancelled.into()));
-                                }
-                            },
-                            None => {
-                                let settings: Settings = Default::default();
-                                if settings.get_element(0) == 0 {
-                                    display::blind_signing_enable_ui(ctx);
-                                    send_data(comm, Err(io::StatusWords::UserCancelled.into()));
-                                } else {
-                                    // Delay lock to prevent the device to pinlock
-                                    #[cfg(any(
-                                        target_os = "stax",
-                                        target_os = "flex",
-                                        target_os = "apex_p"
-                                    ))]
-                                    uxapp::UxEvent::DelayLock.request();
-                                    match display::show_hash(ctx, true) {
-                                        true => {
-                                            rdata.extend_from_slice(ctx.hash.value.as_ref());
-                                            crypto::sign_hash(ctx).unwrap();
-                                            rdata.extend_from_slice([SIG_LENGTH].as_slice());
-                                            rdata.extend_from_slice(ctx.signature.r.as_ref());
-                                            rdata.extend_from_slice(ctx.signature.s.as_ref());
-                                            rdata.extend_from_slice([ctx.signature.v].as_slice());
-                                            display::show_status(true, true, ctx);
-                                            send_data(comm, Ok(Some(rdata)));
-                                        }
-                                        false => {
-                                            display::show_status(false, true, ctx);
-                                            send_data(
-                                                comm,
-                                                Err(io::StatusWords::UserCancelled.into()),
-                                            );
-                                        }
-                                    }
-                                }
-                            }
+                            Some(approved) => sign_and_reply(ctx, comm, approved),
+                            None => blind_sign_and_reply(ctx, comm),
                         }
                     }
                 }
             }
             _ => {
-                send_data(comm, Err(io::StatusWords::BadP1P2.into()));
+                send_data(comm, Err(AppSW::BadP1P2.into()));
             }
         },
         Ins::SignTxV1 => match p1 {
@@ -355,6 +461,7 @@ fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
                 ctx.tx = Transaction::InvokeV1(InvokeTransactionV1::default());
                 match crypto::set_derivation_path(&mut data, ctx) {
                     Ok(()) => {
+                        ctx.tx_state = next_tx_state;
                         send_data(comm, Ok(None));
                     }
                     Err(e) => {
@@ -364,14 +471,27 @@ fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
             }
             1 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                transaction::set_tx_fields(data, &mut ctx.tx);
-                send_data(comm, Ok(None));
+                match transaction::set_tx_fields(data, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
             }
             2 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                let nb_calls = FieldElement::from(data);
-                transaction::set_calldata_nb(&mut ctx.tx, nb_calls);
-                send_data(comm, Ok(None));
+                match transaction::set_calldata_nb(data, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
             }
             3 => {
                 let nb_rcv_calls = match p2 == transaction::SetCallStep::New.into() {
@@ -391,72 +511,31 @@ fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
                 // Delay lock to prevent the device to pinlock
                 #[cfg(any(target_os = "stax", target_os = "flex", target_os = "apex_p"))]
                 uxapp::UxEvent::DelayLock.request();
-                if let Some(err) = transaction::set_calldata(data, p2.into(), &mut ctx.tx).err() {
-                    send_data(comm, Err(Reply(err as u16)));
+                if let Err(e) = transaction::set_calldata(data, p2, &mut ctx.tx) {
+                    send_data(comm, Err(e.into()));
+                    return;
                 }
                 match transaction::tx_complete(&mut ctx.tx) {
                     None => {
+                        ctx.tx_state = next_tx_state;
                         send_data(comm, Ok(None));
                     }
                     Some(hash) => {
                         ctx.hash = hash;
+                        if let Err(e) = display::check_tx(ctx) {
+                            display::show_status(false, true, ctx);
+                            send_data(comm, Err(e.into()));
+                            return;
+                        }
                         match display::show_tx(ctx) {
-                            Some(approved) => match approved {
-                                true => {
-                                    rdata.extend_from_slice(ctx.hash.value.as_ref());
-                                    crypto::sign_hash(ctx).unwrap();
-                                    rdata.extend_from_slice([SIG_LENGTH].as_slice());
-                                    rdata.extend_from_slice(ctx.signature.r.as_ref());
-                                    rdata.extend_from_slice(ctx.signature.s.as_ref());
-                                    rdata.extend_from_slice([ctx.signature.v].as_slice());
-                                    display::show_status(true, true, ctx);
-                                    send_data(comm, Ok(Some(rdata)));
-                                }
-                                false => {
-                                    display::show_status(false, true, ctx);
-                                    send_data(comm, Err(io::StatusWords::UserCancelled.into()));
-                                }
-                            },
-                            None => {
-                                let settings: Settings = Default::default();
-                                if settings.get_element(0) == 0 {
-                                    display::blind_signing_enable_ui(ctx);
-                                    send_data(comm, Err(io::StatusWords::UserCancelled.into()));
-                                } else {
-                                    // Delay lock to prevent the device to pinlock
-                                    #[cfg(any(
-                                        target_os = "stax",
-                                        target_os = "flex",
-                                        target_os = "apex_p"
-                                    ))]
-                                    uxapp::UxEvent::DelayLock.request();
-                                    match display::show_hash(ctx, true) {
-                                        true => {
-                                            rdata.extend_from_slice(ctx.hash.value.as_ref());
-                                            crypto::sign_hash(ctx).unwrap();
-                                            rdata.extend_from_slice([SIG_LENGTH].as_slice());
-                                            rdata.extend_from_slice(ctx.signature.r.as_ref());
-                                            rdata.extend_from_slice(ctx.signature.s.as_ref());
-                                            rdata.extend_from_slice([ctx.signature.v].as_slice());
-                                            display::show_status(true, true, ctx);
-                                            send_data(comm, Ok(Some(rdata)));
-                                        }
-                                        false => {
-                                            display::show_status(false, true, ctx);
-                                            send_data(
-                                                comm,
-                                                Err(io::StatusWords::UserCancelled.into()),
-                                            );
-                                        }
-                                    }
-                                }
-                            }
+                            Some(approved) => sign_and_reply(ctx, comm, approved),
+                            None => blind_sign_and_reply(ctx, comm),
                         }
                     }
                 }
             }
             _ => {
-                send_data(comm, Err(io::StatusWords::BadP1P2.into()));
+                send_data(comm, Err(AppSW::BadP1P2.into()));
             }
         },
         Ins::SignDeployAccount => match p1 {
@@ -466,6 +545,7 @@ fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
                 ctx.tx = Transaction::DeployAccountV3(DeployAccountTransactionV3::default());
                 match crypto::set_derivation_path(&mut data, ctx) {
                     Ok(()) => {
+                        ctx.tx_state = next_tx_state;
                         send_data(comm, Ok(None));
                     }
                     Err(e) => {
@@ -475,62 +555,81 @@ fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
             }
             1 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                transaction::set_tx_fields(data, &mut ctx.tx);
-                send_data(comm, Ok(None));
+                match transaction::set_tx_fields(data, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
             }
             2 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                transaction::set_tx_fees(data, &mut ctx.tx);
-                send_data(comm, Ok(None));
+                match transaction::set_tx_fees(data, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
             }
             3 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                transaction::set_paymaster_data(data, p2, &mut ctx.tx);
-                send_data(comm, Ok(None));
+                match transaction::set_paymaster_data(data, p2, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
             }
             4 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                let constructor_calldata_length = FieldElement::from(data);
-                transaction::set_calldata_nb(&mut ctx.tx, constructor_calldata_length);
-                send_data(comm, Ok(None));
+                match transaction::set_calldata_nb(data, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
             }
             5 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                if let Some(err) = transaction::set_calldata(data, p2.into(), &mut ctx.tx).err() {
-                    send_data(comm, Err(Reply(err as u16)));
+                if let Err(e) = transaction::set_calldata(data, p2, &mut ctx.tx) {
+                    send_data(comm, Err(e.into()));
+                    return;
                 }
                 match transaction::tx_complete(&mut ctx.tx) {
                     None => {
+                        ctx.tx_state = next_tx_state;
                         send_data(comm, Ok(None));
                     }
                     Some(hash) => {
                         ctx.hash = hash;
+                        if let Err(e) = display::check_tx(ctx) {
+                            display::show_status(false, true, ctx);
+                            send_data(comm, Err(e.into()));
+                            return;
+                        }
                         match display::show_tx(ctx) {
-                            Some(approved) => match approved {
-                                true => {
-                                    rdata.extend_from_slice(ctx.hash.value.as_ref());
-                                    crypto::sign_hash(ctx).unwrap();
-                                    rdata.extend_from_slice([SIG_LENGTH].as_slice());
-                                    rdata.extend_from_slice(ctx.signature.r.as_ref());
-                                    rdata.extend_from_slice(ctx.signature.s.as_ref());
-                                    rdata.extend_from_slice([ctx.signature.v].as_slice());
-                                    display::show_status(true, true, ctx);
-                                    send_data(comm, Ok(Some(rdata)));
-                                }
-                                false => {
-                                    display::show_status(false, true, ctx);
-                                    send_data(comm, Err(io::StatusWords::UserCancelled.into()));
-                                }
-                            },
+                            Some(approved) => sign_and_reply(ctx, comm, approved),
                             None => {
-                                send_data(comm, Err(io::StatusWords::UserCancelled.into()));
+                                send_data(comm, Err(AppSW::Deny.into()));
                             }
                         }
                     }
                 }
             }
             _ => {
-                send_data(comm, Err(io::StatusWords::BadP1P2.into()));
+                send_data(comm, Err(AppSW::BadP1P2.into()));
             }
         },
         Ins::SignDeployAccountV1 => match p1 {
@@ -540,6 +639,7 @@ fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
                 ctx.tx = Transaction::DeployAccountV1(DeployAccountTransactionV1::default());
                 match crypto::set_derivation_path(&mut data, ctx) {
                     Ok(()) => {
+                        ctx.tx_state = next_tx_state;
                         send_data(comm, Ok(None));
                     }
                     Err(e) => {
@@ -549,55 +649,253 @@ fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
             }
             1 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                transaction::set_tx_fields(data, &mut ctx.tx);
-                send_data(comm, Ok(None));
+                match transaction::set_tx_fields(data, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
             }
             2 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                let constructor_calldata_length = FieldElement::from(data);
-                transaction::set_calldata_nb(&mut ctx.tx, constructor_calldata_length);
-                send_data(comm, Ok(None));
+                match transaction::set_calldata_nb(data, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
             }
             3 => {
                 display::show_step(PARSING_STEP_TX_WORDING, ctx);
-                if let Some(err) = transaction::set_calldata(data, p2.into(), &mut ctx.tx).err() {
-                    send_data(comm, Err(Reply(err as u16)));
+                if let Err(e) = transaction::set_calldata(data, p2, &mut ctx.tx) {
+                    send_data(comm, Err(e.into()));
+                    return;
                 }
 
                 match transaction::tx_complete(&mut ctx.tx) {
                     None => {
+                        ctx.tx_state = next_tx_state;
                         send_data(comm, Ok(None));
                     }
                     Some(hash) => {
                         ctx.hash = hash;
+                        if let Err(e) = display::check_tx(ctx) {
+                            display::show_status(false, true, ctx);
+                            send_data(comm, Err(e.into()));
+                            return;
+                        }
                         match display::show_tx(ctx) {
-                            Some(approved) => match approved {
-                                true => {
-                                    rdata.extend_from_slice(ctx.hash.value.as_ref());
-                                    crypto::sign_hash(ctx).unwrap();
-                                    rdata.extend_from_slice([SIG_LENGTH].as_slice());
-                                    rdata.extend_from_slice(ctx.signature.r.as_ref());
-                                    rdata.extend_from_slice(ctx.signature.s.as_ref());
-                                    rdata.extend_from_slice([ctx.signature.v].as_slice());
-                                    display::show_status(true, true, ctx);
-                                    send_data(comm, Ok(Some(rdata)));
-                                }
-                                false => {
-                                    display::show_status(false, true, ctx);
-                                    send_data(comm, Err(io::StatusWords::UserCancelled.into()));
-                                }
-                            },
+                            Some(approved) => sign_and_reply(ctx, comm, approved),
                             None => {
-                                send_data(comm, Err(io::StatusWords::UserCancelled.into()));
+                                send_data(comm, Err(AppSW::Deny.into()));
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {
+                send_data(comm, Err(AppSW::BadP1P2.into()));
+            }
+        },
+        Ins::SignDeclare => match p1 {
+            0 => {
+                ctx.reset();
+                ctx.req_type = RequestType::SignDeclare;
+                ctx.tx = Transaction::DeclareV3(DeclareTransactionV3::default());
+                match crypto::set_derivation_path(&mut data, ctx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
+            }
+            1 => {
+                display::show_step(PARSING_STEP_TX_WORDING, ctx);
+                match transaction::set_tx_fields(data, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
+            }
+            2 => {
+                display::show_step(PARSING_STEP_TX_WORDING, ctx);
+                match transaction::set_tx_fees(data, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
+            }
+            3 => {
+                display::show_step(PARSING_STEP_TX_WORDING, ctx);
+                match transaction::set_paymaster_data(data, p2, &mut ctx.tx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
+            }
+            4 => {
+                display::show_step(PARSING_STEP_TX_WORDING, ctx);
+                match transaction::set_account_deployment_data(data, p2, &mut ctx.tx) {
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                    Ok(()) if p2 == transaction::DataChunk::More.into() => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Ok(()) => match transaction::tx_complete(&mut ctx.tx) {
+                        None => {
+                            ctx.tx_state = next_tx_state;
+                            send_data(comm, Ok(None));
+                        }
+                        Some(hash) => {
+                            ctx.hash = hash;
+                            if let Err(e) = display::check_tx(ctx) {
+                                display::show_status(false, true, ctx);
+                                send_data(comm, Err(e.into()));
+                                return;
                             }
+                            match display::show_tx(ctx) {
+                                Some(approved) => sign_and_reply(ctx, comm, approved),
+                                None => {
+                                    send_data(comm, Err(AppSW::Deny.into()));
+                                }
+                            }
+                        }
+                    },
+                }
+            }
+            _ => {
+                send_data(comm, Err(AppSW::BadP1P2.into()));
+            }
+        },
+        Ins::SignTypedData => match p1 {
+            0 => {
+                ctx.reset();
+                ctx.req_type = RequestType::SignTypedData;
+                match crypto::set_derivation_path(&mut data, ctx) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                }
+            }
+            1 => match typed_data::set_account_address(data, &mut ctx.typed_data) {
+                Ok(()) => {
+                    ctx.tx_state = next_tx_state;
+                    send_data(comm, Ok(None));
+                }
+                Err(e) => send_data(comm, Err(e.into())),
+            },
+            2 => match typed_data::add_definition(data, &mut ctx.typed_data) {
+                Ok(()) => {
+                    ctx.tx_state = next_tx_state;
+                    send_data(comm, Ok(None));
+                }
+                Err(e) => send_data(comm, Err(e.into())),
+            },
+            3 => {
+                display::show_step(PARSING_STEP_MESSAGE_WORDING, ctx);
+                match typed_data::start_struct(data, &mut ctx.typed_data) {
+                    Ok(()) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Err(e) => send_data(comm, Err(e.into())),
+                }
+            }
+            4 => {
+                // Delay lock to prevent the device to pinlock
+                #[cfg(any(target_os = "stax", target_os = "flex", target_os = "apex_p"))]
+                uxapp::UxEvent::DelayLock.request();
+                match typed_data::set_value(data, &mut ctx.typed_data) {
+                    Err(e) => {
+                        send_data(comm, Err(e.into()));
+                    }
+                    Ok(None) => {
+                        ctx.tx_state = next_tx_state;
+                        send_data(comm, Ok(None));
+                    }
+                    Ok(Some(hash)) => {
+                        ctx.hash = hash;
+                        match display::show_typed_data(ctx) {
+                            Some(approved) => sign_and_reply(ctx, comm, approved),
+                            None => blind_sign_and_reply(ctx, comm),
                         }
                     }
                 }
             }
             _ => {
-                send_data(comm, Err(io::StatusWords::BadP1P2.into()));
+                send_data(comm, Err(AppSW::BadP1P2.into()));
             }
         },
+        Ins::ProvideTokenInfo => match erc20::provide_token_info(data, &mut ctx.tokens) {
+            Ok(()) => send_data(comm, Ok(None)),
+            Err(e) => send_data(comm, Err(e.into())),
+        },
+        Ins::ProvideCallDescriptor => {
+            let res = match p1 {
+                0 => descriptor::start_descriptor(data, &mut ctx.descriptors),
+                1 => descriptor::add_param(data, &mut ctx.descriptors),
+                _ => descriptor::end_descriptor(data, &mut ctx.descriptors),
+            };
+            match res {
+                Ok(()) => send_data(comm, Ok(None)),
+                Err(e) => send_data(comm, Err(e.into())),
+            }
+        }
+        Ins::GetAccountAddress { display, class } => {
+            ctx.reset();
+            ctx.req_type = RequestType::GetAccountAddress;
+
+            if let Err(e) = crypto::set_derivation_path(&mut data, ctx) {
+                send_data(comm, Err(e.into()));
+                return;
+            }
+            match crypto::get_pubkey(ctx) {
+                Err(e) => {
+                    send_data(comm, Err(Reply::from(e)));
+                }
+                Ok(key) => {
+                    let pub_key = types::FieldElement::from(&key.as_ref()[1..33]);
+                    let address = class.address(&pub_key);
+                    let ret = match display {
+                        false => true,
+                        true => display::account_address_ui(*class, &address, ctx),
+                    };
+                    if ret {
+                        rdata.extend_from_slice(address.value.as_ref());
+                        send_data(comm, Ok(Some(rdata)));
+                    } else {
+                        send_data(comm, Err(AppSW::Deny.into()));
+                    }
+                }
+            }
+        }
         #[cfg(feature = "poseidon")]
         Ins::Poseidon => {
             let data = comm.get_data()?;
@@ -644,7 +942,7 @@ fn handle_apdu(comm: &mut io::Comm, ins: &Ins, ctx: &mut Ctx) {
                     comm.append(hasher.finalize().value.as_ref());
                 }
                 _ => {
-                    return Err(io::StatusWords::BadP1P2.into());
+                    return Err(AppSW::BadP1P2.into());
                 }
             }
         }
@@ -1,6 +1,17 @@
 use ledger_device_sdk::nvm::*;
 use ledger_device_sdk::NVMData;
 
+/// Index of each setting in the NVM storage, NBGL switches being stored in the same order
+pub const BLIND_SIGNING: usize = 0;
+/// Cap level of STRK fees, see `fees::FeeToken::cap`
+pub const STRK_FEE_CAP: usize = 1;
+/// Cap level of ETH fees
+pub const ETH_FEE_CAP: usize = 2;
+/// Reject transactions over the fee cap instead of warning
+pub const REJECT_OVER_FEE_CAP: usize = 3;
+/// Show addresses in blocks of 4 characters instead of in full
+pub const GROUPED_ADDRESSES: usize = 4;
+
 // This is necessary to store the object in NVM and not in RAM
 const SETTINGS_SIZE: usize = 10;
 #[link_section = ".nvm_data"]
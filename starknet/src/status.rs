@@ -0,0 +1,64 @@
+use ledger_device_sdk::io::Reply;
+
+/// Status words returned by the app, see the list of return codes in docs/apdu.md
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AppSW {
+    /// Rejected by the user, the UserCancelled status word of the SDK
+    Deny = 0x6E04,
+    BadIns = 0x6E01,
+    BadP1P2 = 0x6E02,
+
+    InvalidPathPrefix = 0xFF00,
+    InvalidPathLength = 0xFF01,
+    SignFailed = 0xFF02,
+
+    TypedDataInvalidDefinition = 0xFF10,
+    TypedDataUnknownType = 0xFF11,
+    TypedDataInvalidValue = 0xFF12,
+    TypedDataUnexpectedData = 0xFF13,
+
+    TokenInfoInvalidData = 0xFF20,
+    TokenInfoInvalidSignature = 0xFF21,
+
+    DescriptorInvalidData = 0xFF30,
+    DescriptorInvalidSignature = 0xFF31,
+    DescriptorUnexpectedData = 0xFF32,
+
+    /// Too much paymaster or account deployment data
+    TooMuchData = 0xFF40,
+
+    InvalidTip = 0xFF50,
+    InvalidResourceBounds = 0xFF51,
+
+    UnknownDaMode = 0xFF60,
+
+    /// Payload is not made of the expected number of felts
+    InvalidTxLength = 0xFF70,
+    /// Data sent for a field the transaction does not have
+    UnexpectedTxField = 0xFF71,
+    /// Transaction version whose hash the app cannot compute
+    UnsupportedTxVersion = 0xFF72,
+    /// p2 is not a valid SetCallStep or DataChunk
+    InvalidTxStep = 0xFF73,
+    /// More calldata than announced for a call
+    CalldataOverflow = 0xFF74,
+    /// More calls than announced for a transaction
+    TooManyCalls = 0xFF75,
+    /// Count or length too large to be handled
+    InvalidData = 0xFF76,
+
+    /// Transaction signing step received out of order
+    WrongState = 0xFF80,
+
+    BlindSigningDisabled = 0xFF90,
+    /// Transaction for an unknown chain refused by the user
+    UnknownChain = 0xFF91,
+    /// Max fee over the cap set in the settings
+    FeeCapExceeded = 0xFF92,
+}
+
+impl From<AppSW> for Reply {
+    fn from(sw: AppSW) -> Reply {
+        Reply(sw as u16)
+    }
+}
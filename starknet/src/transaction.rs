@@ -1,9 +1,13 @@
 use crate::{
     context::{
-        Call, DeployAccountTransactionV1, DeployAccountTransactionV3, InvokeTransactionV1,
-        InvokeTransactionV3, Transaction,
+        Call, CallSummary, DeclareTransactionV3, DeployAccountTransactionV1,
+        DeployAccountTransactionV3, InvokeTransactionV1, InvokeTransactionV3, Transaction,
+        MAX_ACCOUNT_DEPLOYMENT_DATA, MAX_CALLDATA, MAX_CALLS, MAX_CALL_SUMMARIES,
+        MAX_PAYMASTER_DATA,
     },
     crypto::{self, HasherTrait},
+    fees::Fees,
+    status::AppSW,
     types::FieldElement,
 };
 
@@ -23,16 +27,31 @@ impl From<TxVersion> for FieldElement {
     }
 }
 
-impl From<FieldElement> for TxVersion {
-    fn from(version: FieldElement) -> Self {
-        match version.into() {
-            1u8 => TxVersion::V1,
-            3u8 => TxVersion::V3,
-            _ => panic!("Invalid transaction version"),
+impl TryFrom<FieldElement> for TxVersion {
+    type Error = AppSW;
+    fn try_from(version: FieldElement) -> Result<Self, Self::Error> {
+        if version == FieldElement::from(TxVersion::V1) {
+            Ok(TxVersion::V1)
+        } else if version == FieldElement::from(TxVersion::V3) {
+            Ok(TxVersion::V3)
+        } else {
+            Err(AppSW::UnsupportedTxVersion)
         }
     }
 }
 
+/// Split a payload made of exactly N felts
+fn felts<const N: usize>(data: &[u8]) -> Result<[FieldElement; N], AppSW> {
+    if data.len() != N * FIELD_ELEMENT_SIZE {
+        return Err(AppSW::InvalidTxLength);
+    }
+    let mut felts = [FieldElement::ZERO; N];
+    for (felt, d) in felts.iter_mut().zip(data.chunks(FIELD_ELEMENT_SIZE)) {
+        *felt = d.into();
+    }
+    Ok(felts)
+}
+
 pub fn tx_complete(tx: &mut Transaction) -> Option<FieldElement> {
     match tx {
         Transaction::InvokeV3(tx) => {
@@ -48,7 +67,7 @@ pub fn tx_complete(tx: &mut Transaction) -> Option<FieldElement> {
             if (tx.nb_rcv_calls == tx.nb_calls) && (tx.call.nb_rcv_calldata == tx.call.nb_calldata)
             {
                 tx.hasher_calldata
-                    .update(FieldElement::from(tx.hasher_calldata.get_nb_fe() as u8));
+                    .update(FieldElement::from(tx.hasher_calldata.get_nb_fe()));
                 let hash_calldata = tx.hasher_calldata.finalize();
                 tx.hasher.update(hash_calldata);
                 tx.hasher.update(tx.max_fee);
@@ -69,7 +88,10 @@ pub fn tx_complete(tx: &mut Transaction) -> Option<FieldElement> {
             None
         }
         Transaction::DeployAccountV1(tx) => {
-            if tx.constructor_calldata.len() == tx.constructor_calldata.capacity() {
+            if tx.nb_rcv_constructor_calldata == tx.nb_constructor_calldata {
+                tx.hasher_calldata
+                    .update(FieldElement::from(2usize + tx.nb_constructor_calldata));
+                tx.hasher.update(tx.hasher_calldata.finalize());
                 tx.hasher.update(tx.max_fee);
                 tx.hasher.update(tx.chain_id);
                 tx.hasher.update(tx.nonce);
@@ -78,185 +100,296 @@ pub fn tx_complete(tx: &mut Transaction) -> Option<FieldElement> {
             }
             None
         }
+        // Complete once the account deployment data, last of the streamed fields, is received
+        Transaction::DeclareV3(tx) => {
+            tx.hasher.update(tx.class_hash);
+            tx.hasher.update(tx.compiled_class_hash);
+            Some(tx.hasher.finalize())
+        }
         Transaction::None => None,
     }
 }
 
-pub fn set_tx_fields(data: &[u8], tx: &mut Transaction) {
+pub fn set_tx_fields(data: &[u8], tx: &mut Transaction) -> Result<(), AppSW> {
     match tx {
         Transaction::InvokeV3(tx) => set_invoke_fields_v3(data, tx),
         Transaction::InvokeV1(tx) => set_invoke_fields_v1(data, tx),
         Transaction::DeployAccountV3(tx) => set_deploy_account_fields_v3(data, tx),
         Transaction::DeployAccountV1(tx) => set_deploy_account_fields_v1(data, tx),
-        Transaction::None => panic!("Invalid transaction type"),
+        Transaction::DeclareV3(tx) => set_declare_fields_v3(data, tx),
+        Transaction::None => Err(AppSW::UnexpectedTxField),
     }
 }
 
-pub fn set_tx_fees(data: &[u8], tx: &mut Transaction) {
-    match tx {
-        Transaction::DeployAccountV3(tx) => {
-            let mut fee_hasher = crypto::poseidon::PoseidonHasher::default();
-            let mut iter = data.chunks(FIELD_ELEMENT_SIZE);
-            tx.tip = iter.next().unwrap().into();
-            fee_hasher.update(tx.tip);
-            tx.l1_gas_bounds = iter.next().unwrap().into();
-            fee_hasher.update(tx.l1_gas_bounds);
-            tx.l2_gas_bounds = iter.next().unwrap().into();
-            fee_hasher.update(tx.l2_gas_bounds);
-            tx.l1_data_gas_bounds = match iter.next() {
-                Some(data) => {
-                    fee_hasher.update(data.into());
-                    data.into()
-                }
-                None => FieldElement::ZERO,
-            };
-            let fee_hash = fee_hasher.finalize();
-            tx.hasher.update(fee_hash);
-        }
-        Transaction::InvokeV3(tx) => {
-            let mut fee_hasher = crypto::poseidon::PoseidonHasher::default();
-            let mut iter = data.chunks(FIELD_ELEMENT_SIZE);
-            tx.tip = iter.next().unwrap().into();
-            fee_hasher.update(tx.tip);
-            tx.l1_gas_bounds = iter.next().unwrap().into();
-            fee_hasher.update(tx.l1_gas_bounds);
-            tx.l2_gas_bounds = iter.next().unwrap().into();
-            fee_hasher.update(tx.l2_gas_bounds);
-            tx.l1_data_gas_bounds = match iter.next() {
-                Some(data) => {
-                    ledger_device_sdk::testing::debug_print("we have l1_data_gas filed\n");
-                    fee_hasher.update(data.into());
-                    data.into()
-                }
-                None => FieldElement::ZERO,
-            };
-            let fee_hash = fee_hasher.finalize();
-            tx.hasher.update(fee_hash);
+/// Data availability mode of the nonce or of the fee of a V3 transaction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DaMode {
+    L1 = 0,
+    L2 = 1,
+}
+
+impl core::fmt::Display for DaMode {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            DaMode::L1 => write!(f, "L1"),
+            DaMode::L2 => write!(f, "L2"),
         }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DaModes {
+    pub nonce: DaMode,
+    pub fee: DaMode,
+}
+
+/// Decode the data_availability_mode field: nonce mode << 32 | fee mode
+pub fn decode_da_modes(data_availability_mode: &FieldElement) -> Result<DaModes, AppSW> {
+    let value = &data_availability_mode.value;
+    if value[0..24].iter().any(|b| *b != 0) {
+        return Err(AppSW::UnknownDaMode);
+    }
+    let mode = |bytes: &[u8]| match u32::from_be_bytes(bytes.try_into().unwrap()) {
+        0 => Ok(DaMode::L1),
+        1 => Ok(DaMode::L2),
+        _ => Err(AppSW::UnknownDaMode),
+    };
+    Ok(DaModes {
+        nonce: mode(&value[24..28])?,
+        fee: mode(&value[28..32])?,
+    })
+}
+
+pub fn set_tx_fees(data: &[u8], tx: &mut Transaction) -> Result<(), AppSW> {
+    let (fees, hasher) = match tx {
+        Transaction::InvokeV3(tx) => (&mut tx.fees, &mut tx.hasher),
+        Transaction::DeployAccountV3(tx) => (&mut tx.fees, &mut tx.hasher),
+        Transaction::DeclareV3(tx) => (&mut tx.fees, &mut tx.hasher),
         Transaction::InvokeV1(_) | Transaction::DeployAccountV1(_) | Transaction::None => {
-            panic!("Invalid transaction type")
+            return Err(AppSW::UnexpectedTxField)
         }
+    };
+
+    *fees = Fees::parse(data)?;
+    let mut fee_hasher = crypto::poseidon::PoseidonHasher::default();
+    for d in data.chunks(FIELD_ELEMENT_SIZE) {
+        fee_hasher.update(d.into());
     }
+    hasher.update(fee_hasher.finalize());
+    Ok(())
 }
 
-fn set_invoke_fields_v3(data: &[u8], tx: &mut InvokeTransactionV3) {
+fn set_invoke_fields_v3(data: &[u8], tx: &mut InvokeTransactionV3) -> Result<(), AppSW> {
+    let [sender_address, chain_id, nonce, data_availability_mode] = felts(data)?;
+    decode_da_modes(&data_availability_mode)?;
     tx.version = FieldElement::from(TxVersion::V3 as u8);
-    let mut iter = data.chunks(FIELD_ELEMENT_SIZE);
-    tx.sender_address = iter.next().unwrap().into();
-    tx.chain_id = iter.next().unwrap().into();
-    tx.nonce = iter.next().unwrap().into();
-    tx.data_availability_mode = iter.next().unwrap().into();
+    tx.sender_address = sender_address;
+    tx.chain_id = chain_id;
+    tx.nonce = nonce;
+    tx.data_availability_mode = data_availability_mode;
 
     // Update hasher
     tx.hasher.update(FieldElement::INVOKE);
     tx.hasher.update(tx.version);
     tx.hasher.update(tx.sender_address);
+    Ok(())
 }
 
-fn set_invoke_fields_v1(data: &[u8], tx: &mut InvokeTransactionV1) {
+fn set_invoke_fields_v1(data: &[u8], tx: &mut InvokeTransactionV1) -> Result<(), AppSW> {
+    let [sender_address, max_fee, chain_id, nonce] = felts(data)?;
     tx.version = FieldElement::from(TxVersion::V1 as u8);
-    let mut iter = data.chunks(FIELD_ELEMENT_SIZE);
-    tx.sender_address = iter.next().unwrap().into();
-    tx.max_fee = iter.next().unwrap().into();
-    tx.chain_id = iter.next().unwrap().into();
-    tx.nonce = iter.next().unwrap().into();
+    tx.sender_address = sender_address;
+    tx.max_fee = max_fee;
+    tx.chain_id = chain_id;
+    tx.nonce = nonce;
 
     // Update hasher
     tx.hasher.update(FieldElement::INVOKE);
     tx.hasher.update(tx.version);
     tx.hasher.update(tx.sender_address);
     tx.hasher.update(FieldElement::ZERO);
+    Ok(())
 }
 
-// For future use: currently paymaster_data is always empty.
-// See https://docs.starknet.io/architecture-and-concepts/network-architecture/transactions/#v3_transaction_fields
-pub fn set_paymaster_data(_data: &[u8], _p2: u8, tx: &mut Transaction) {
-    match tx {
-        Transaction::InvokeV3(tx) => {
-            let paymaster_hash =
-                crate::crypto::poseidon::PoseidonStark252::hash_many(&tx.paymaster_data);
-            tx.hasher.update(paymaster_hash);
-            tx.hasher.update(tx.chain_id);
-            tx.hasher.update(tx.nonce);
-            tx.hasher.update(tx.data_availability_mode);
-        }
+/// Receive paymaster_data, streamed over one or more APDUs.
+/// It is hashed once its last chunk (see DataChunk) is received.
+/// See https://docs.starknet.io/architecture-and-concepts/network-architecture/transactions/#v3_transaction_fields
+pub fn set_paymaster_data(data: &[u8], p2: u8, tx: &mut Transaction) -> Result<(), AppSW> {
+    let (paymaster_data, hasher, chain_id, nonce, data_availability_mode) = match tx {
+        Transaction::InvokeV3(tx) => (
+            &mut tx.paymaster_data,
+            &mut tx.hasher,
+            tx.chain_id,
+            tx.nonce,
+            tx.data_availability_mode,
+        ),
+        Transaction::DeployAccountV3(tx) => (
+            &mut tx.paymaster_data,
+            &mut tx.hasher,
+            tx.chain_id,
+            tx.nonce,
+            tx.data_availability_mode,
+        ),
+        Transaction::DeclareV3(tx) => (
+            &mut tx.paymaster_data,
+            &mut tx.hasher,
+            tx.chain_id,
+            tx.nonce,
+            tx.data_availability_mode,
+        ),
+        _ => return Err(AppSW::UnexpectedTxField),
+    };
 
-        Transaction::DeployAccountV3(tx) => {
-            let paymaster_hash =
-                crate::crypto::poseidon::PoseidonStark252::hash_many(&tx.paymaster_data);
-            tx.hasher.update(paymaster_hash);
-            tx.hasher.update(tx.chain_id);
-            tx.hasher.update(tx.nonce);
-            tx.hasher.update(tx.data_availability_mode);
-        }
-        _ => panic!("Invalid transaction type"),
+    let chunk = DataChunk::try_from(p2)?;
+    append_data(data, paymaster_data, MAX_PAYMASTER_DATA)?;
+    if chunk == DataChunk::Last {
+        let paymaster_hash = crate::crypto::poseidon::PoseidonStark252::hash_many(paymaster_data);
+        hasher.update(paymaster_hash);
+        hasher.update(chain_id);
+        hasher.update(nonce);
+        hasher.update(data_availability_mode);
     }
+    Ok(())
 }
 
-// For future use: currently account_deployment_data is always empty.
-// See https://docs.starknet.io/architecture-and-concepts/network-architecture/transactions/#v3_transaction_fields
-pub fn set_account_deployment_data(_data: &[u8], _p2: u8, tx: &mut Transaction) {
-    match tx {
-        Transaction::InvokeV3(tx) => {
-            let account_deployment_hash =
-                crate::crypto::poseidon::PoseidonStark252::hash_many(&tx.account_deployment_data);
-            tx.hasher.update(account_deployment_hash);
-        }
-        _ => panic!("Invalid transaction type"),
+/// Append the felts of a chunk of data, which cannot exceed max felts in total
+fn append_data(data: &[u8], list: &mut Vec<FieldElement>, max: usize) -> Result<(), AppSW> {
+    if data.len() % FIELD_ELEMENT_SIZE != 0 || list.len() + data.len() / FIELD_ELEMENT_SIZE > max {
+        return Err(AppSW::TooMuchData);
+    }
+    for d in data.chunks(FIELD_ELEMENT_SIZE) {
+        list.push(d.into());
     }
+    Ok(())
 }
 
-fn set_deploy_account_fields_v3(data: &[u8], tx: &mut DeployAccountTransactionV3) {
+/// Receive account_deployment_data, streamed over one or more APDUs.
+/// It is hashed once its last chunk (see DataChunk) is received.
+/// See https://docs.starknet.io/architecture-and-concepts/network-architecture/transactions/#v3_transaction_fields
+pub fn set_account_deployment_data(data: &[u8], p2: u8, tx: &mut Transaction) -> Result<(), AppSW> {
+    let (account_deployment_data, hasher) = match tx {
+        Transaction::InvokeV3(tx) => (&mut tx.account_deployment_data, &mut tx.hasher),
+        Transaction::DeclareV3(tx) => (&mut tx.account_deployment_data, &mut tx.hasher),
+        _ => return Err(AppSW::UnexpectedTxField),
+    };
+
+    let chunk = DataChunk::try_from(p2)?;
+    append_data(data, account_deployment_data, MAX_ACCOUNT_DEPLOYMENT_DATA)?;
+    if chunk == DataChunk::Last {
+        let account_deployment_hash =
+            crate::crypto::poseidon::PoseidonStark252::hash_many(account_deployment_data);
+        hasher.update(account_deployment_hash);
+    }
+    Ok(())
+}
+
+fn set_declare_fields_v3(data: &[u8], tx: &mut DeclareTransactionV3) -> Result<(), AppSW> {
+    let [sender_address, chain_id, nonce, data_availability_mode, class_hash, compiled_class_hash] =
+        felts(data)?;
+    decode_da_modes(&data_availability_mode)?;
     tx.version = FieldElement::from(TxVersion::V3 as u8);
-    let mut iter = data.chunks(FIELD_ELEMENT_SIZE);
-    tx.contract_address = iter.next().unwrap().into();
-    tx.chain_id = iter.next().unwrap().into();
-    tx.nonce = iter.next().unwrap().into();
-    tx.data_availability_mode = iter.next().unwrap().into();
-    tx.class_hash = iter.next().unwrap().into();
-    tx.contract_address_salt = iter.next().unwrap().into();
+    tx.sender_address = sender_address;
+    tx.chain_id = chain_id;
+    tx.nonce = nonce;
+    tx.data_availability_mode = data_availability_mode;
+    tx.class_hash = class_hash;
+    tx.compiled_class_hash = compiled_class_hash;
+
+    // Update hasher
+    tx.hasher.update(FieldElement::DECLARE);
+    tx.hasher.update(tx.version);
+    tx.hasher.update(tx.sender_address);
+    Ok(())
+}
+
+fn set_deploy_account_fields_v3(
+    data: &[u8],
+    tx: &mut DeployAccountTransactionV3,
+) -> Result<(), AppSW> {
+    let [contract_address, chain_id, nonce, data_availability_mode, class_hash, contract_address_salt] =
+        felts(data)?;
+    decode_da_modes(&data_availability_mode)?;
+    tx.version = FieldElement::from(TxVersion::V3 as u8);
+    tx.contract_address = contract_address;
+    tx.chain_id = chain_id;
+    tx.nonce = nonce;
+    tx.data_availability_mode = data_availability_mode;
+    tx.class_hash = class_hash;
+    tx.contract_address_salt = contract_address_salt;
 
     // Update hasher
     tx.hasher.update(FieldElement::DEPLOY_ACCOUNT);
     tx.hasher.update(tx.version);
     tx.hasher.update(tx.contract_address);
+    Ok(())
 }
 
-fn set_deploy_account_fields_v1(data: &[u8], tx: &mut DeployAccountTransactionV1) {
+fn set_deploy_account_fields_v1(
+    data: &[u8],
+    tx: &mut DeployAccountTransactionV1,
+) -> Result<(), AppSW> {
+    let [contract_address, class_hash, contract_address_salt, max_fee, chain_id, nonce] =
+        felts(data)?;
     tx.version = FieldElement::from(TxVersion::V1);
-    let mut iter = data.chunks(FIELD_ELEMENT_SIZE);
-    tx.contract_address = iter.next().unwrap().into();
-    tx.class_hash = iter.next().unwrap().into();
-    tx.contract_address_salt = iter.next().unwrap().into();
-    tx.max_fee = iter.next().unwrap().into();
-    tx.chain_id = iter.next().unwrap().into();
-    tx.nonce = iter.next().unwrap().into();
+    tx.contract_address = contract_address;
+    tx.class_hash = class_hash;
+    tx.contract_address_salt = contract_address_salt;
+    tx.max_fee = max_fee;
+    tx.chain_id = chain_id;
+    tx.nonce = nonce;
 
     // Update hasher
     tx.hasher.update(FieldElement::DEPLOY_ACCOUNT);
     tx.hasher.update(tx.version);
     tx.hasher.update(tx.contract_address);
     tx.hasher.update(FieldElement::ZERO);
+    tx.hasher_calldata.update(tx.class_hash);
+    tx.hasher_calldata.update(tx.contract_address_salt);
+    Ok(())
+}
+
+/// p2 of the APDUs streaming paymaster_data and account_deployment_data
+#[derive(PartialEq)]
+pub enum DataChunk {
+    /// Last (or only) chunk, the data is complete
+    Last = 0x00,
+    /// More chunks follow
+    More = 0x01,
 }
 
-#[allow(dead_code)]
-#[derive(Debug)]
-pub enum SetCallError {
-    TooManyCalls = 0xFF01,
+impl TryFrom<u8> for DataChunk {
+    type Error = AppSW;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(DataChunk::Last),
+            0x01 => Ok(DataChunk::More),
+            _ => Err(AppSW::InvalidTxStep),
+        }
+    }
 }
 
-#[derive(PartialEq)]
+impl From<DataChunk> for u8 {
+    fn from(value: DataChunk) -> Self {
+        match value {
+            DataChunk::Last => 0x00,
+            DataChunk::More => 0x01,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
 pub enum SetCallStep {
     New = 0x00,
     Add = 0x01,
 }
 
-impl From<u8> for SetCallStep {
-    fn from(value: u8) -> Self {
+impl TryFrom<u8> for SetCallStep {
+    type Error = AppSW;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            0x00 => SetCallStep::New,
-            0x01 => SetCallStep::Add,
-            _ => panic!("Invalid SetCallStep value"),
+            0x00 => Ok(SetCallStep::New),
+            0x01 => Ok(SetCallStep::Add),
+            _ => Err(AppSW::InvalidTxStep),
         }
     }
 }
@@ -270,116 +403,171 @@ impl From<SetCallStep> for u8 {
     }
 }
 
-pub fn set_calldata_nb(tx: &mut Transaction, nb: FieldElement) {
+pub fn set_calldata_nb(data: &[u8], tx: &mut Transaction) -> Result<(), AppSW> {
+    let [nb] = felts(data)?;
+    let count = usize::try_from(nb)?;
     match tx {
         Transaction::InvokeV3(tx) => {
-            tx.nb_calls = nb.into();
+            tx.nb_calls = count;
             tx.hasher_calldata.update(nb);
         }
         Transaction::InvokeV1(tx) => {
-            tx.nb_calls = nb.into();
+            tx.nb_calls = count;
             tx.hasher_calldata.update(nb);
         }
         Transaction::DeployAccountV3(tx) => {
-            tx.nb_constructor_calldata = nb.into();
+            tx.nb_constructor_calldata = count;
         }
         Transaction::DeployAccountV1(tx) => {
-            tx.constructor_calldata = Vec::with_capacity(nb.into());
+            tx.nb_constructor_calldata = count;
         }
-        Transaction::None => panic!("Invalid transaction type"),
+        Transaction::DeclareV3(_) | Transaction::None => return Err(AppSW::UnexpectedTxField),
     }
+    Ok(())
 }
 
-pub fn set_calldata(
-    data: &[u8],
-    p2: SetCallStep,
-    tx: &mut Transaction,
-) -> Result<(), SetCallError> {
+pub fn set_calldata(data: &[u8], p2: u8, tx: &mut Transaction) -> Result<(), AppSW> {
+    let step = SetCallStep::try_from(p2)?;
+    if data.len() % FIELD_ELEMENT_SIZE != 0 {
+        return Err(AppSW::InvalidTxLength);
+    }
     match tx {
-        Transaction::InvokeV3(tx) => set_calldata_invoke(
+        Transaction::InvokeV3(tx) => {
+            set_calldata_invoke(
+                data,
+                step,
+                &mut tx.call,
+                &mut tx.calls,
+                &mut tx.hasher_calldata,
+                tx.nb_calls,
+                &mut tx.nb_rcv_calls,
+            )?;
+            keep_call_summary(step, &tx.call, &mut tx.call_summaries);
+            Ok(())
+        }
+        Transaction::InvokeV1(tx) => {
+            set_calldata_invoke(
+                data,
+                step,
+                &mut tx.call,
+                &mut tx.calls,
+                &mut tx.hasher_calldata,
+                tx.nb_calls,
+                &mut tx.nb_rcv_calls,
+            )?;
+            keep_call_summary(step, &tx.call, &mut tx.call_summaries);
+            Ok(())
+        }
+        Transaction::DeployAccountV3(tx) => set_constructor_calldata(
             data,
-            p2,
-            &mut tx.call,
+            &mut tx.constructor_calldata,
             &mut tx.hasher_calldata,
-            &mut tx.nb_rcv_calls,
+            &mut tx.hasher_address,
+            tx.nb_constructor_calldata,
+            &mut tx.nb_rcv_constructor_calldata,
         ),
-        Transaction::InvokeV1(tx) => set_calldata_invoke(
+        Transaction::DeployAccountV1(tx) => set_constructor_calldata(
             data,
-            p2,
-            &mut tx.call,
+            &mut tx.constructor_calldata,
             &mut tx.hasher_calldata,
-            &mut tx.nb_rcv_calls,
+            &mut tx.hasher_address,
+            tx.nb_constructor_calldata,
+            &mut tx.nb_rcv_constructor_calldata,
         ),
-        Transaction::DeployAccountV3(tx) => {
-            let iter = data.chunks(FIELD_ELEMENT_SIZE);
-            for d in iter {
-                tx.hasher_calldata.update(d.into());
-                tx.nb_rcv_constructor_calldata += 1;
-            }
-            Ok(())
-        }
-        Transaction::DeployAccountV1(tx) => {
-            let _ = set_calldata_deploy_account_v1(data, &mut tx.constructor_calldata);
+        Transaction::DeclareV3(_) | Transaction::None => Err(AppSW::UnexpectedTxField),
+    }
+}
 
-            let mut hasher = crypto::pedersen::PedersenHasher::default();
-            hasher.update(tx.class_hash);
-            hasher.update(tx.contract_address_salt);
-            for d in &tx.constructor_calldata {
-                hasher.update(*d);
-            }
-            hasher.update(FieldElement::from(2usize + tx.constructor_calldata.len()));
-            tx.hasher.update(hasher.finalize());
-            Ok(())
+/// Keep the summary of a new call, reviewed if the transaction cannot be clear signed
+fn keep_call_summary(step: SetCallStep, call: &Call, summaries: &mut Vec<CallSummary>) {
+    if step == SetCallStep::New && summaries.len() < MAX_CALL_SUMMARIES {
+        summaries.push(CallSummary {
+            to: call.to,
+            selector: call.selector,
+            nb_calldata: call.nb_calldata,
+        });
+    }
+}
+
+fn set_constructor_calldata(
+    data: &[u8],
+    calldata: &mut Vec<FieldElement>,
+    hasher: &mut impl HasherTrait,
+    hasher_address: &mut crypto::pedersen::PedersenHasher,
+    nb_calldata: usize,
+    nb_rcv_calldata: &mut usize,
+) -> Result<(), AppSW> {
+    if data.len() / FIELD_ELEMENT_SIZE > nb_calldata - *nb_rcv_calldata {
+        return Err(AppSW::CalldataOverflow);
+    }
+    for d in data.chunks(FIELD_ELEMENT_SIZE) {
+        // Only the first calldata felts are needed to check the account owner
+        if calldata.len() < MAX_CALLDATA {
+            calldata.push(d.into());
         }
-        Transaction::None => panic!("Invalid transaction type"),
+        hasher.update(d.into());
+        hasher_address.update(d.into());
+        *nb_rcv_calldata += 1;
     }
+    Ok(())
 }
 
 fn set_calldata_invoke(
     data: &[u8],
     p2: SetCallStep,
     call: &mut Call,
+    calls: &mut Vec<Call>,
     hasher: &mut impl HasherTrait,
+    nb_calls: usize,
     nb_rcv_calls: &mut usize,
-) -> Result<(), SetCallError> {
-    match p2 {
-        SetCallStep::New => {
-            let mut iter = data.chunks(FIELD_ELEMENT_SIZE);
-            call.to = iter.next().unwrap().into();
-            hasher.update(call.to);
-            call.selector = iter.next().unwrap().into();
-            hasher.update(call.selector);
-            let calldata_len = iter.next().unwrap().into();
-            hasher.update(calldata_len);
-            call.nb_calldata = calldata_len.into();
-            call.calldata = Vec::default();
-            call.nb_rcv_calldata = 0;
-            for d in iter {
-                call.calldata.push(d.into());
-                hasher.update(d.into());
-                call.nb_rcv_calldata += 1;
-            }
-            *nb_rcv_calls += 1;
-            Ok(())
+) -> Result<(), AppSW> {
+    let mut iter = data.chunks(FIELD_ELEMENT_SIZE);
+    // Check the call count and calldata length before hashing anything so that an
+    // overflowing request cannot hash data which is not displayed
+    if p2 == SetCallStep::New {
+        let (Some(to), Some(selector), Some(calldata_len)) =
+            (iter.next(), iter.next(), iter.next())
+        else {
+            return Err(AppSW::InvalidTxLength);
+        };
+        // The previous call must be complete
+        if call.nb_rcv_calldata != call.nb_calldata {
+            return Err(AppSW::WrongState);
         }
-        SetCallStep::Add => {
-            let iter = data.chunks(FIELD_ELEMENT_SIZE);
-            for d in iter {
-                hasher.update(d.into());
-                call.nb_rcv_calldata += 1;
-            }
-            Ok(())
+        if *nb_rcv_calls >= nb_calls {
+            return Err(AppSW::TooManyCalls);
         }
+        let calldata_len = FieldElement::from(calldata_len);
+        let nb_calldata = usize::try_from(calldata_len)?;
+        if iter.len() > nb_calldata {
+            return Err(AppSW::CalldataOverflow);
+        }
+        call.to = to.into();
+        hasher.update(call.to);
+        call.selector = selector.into();
+        hasher.update(call.selector);
+        hasher.update(calldata_len);
+        call.nb_calldata = nb_calldata;
+        call.calldata = Vec::default();
+        call.nb_rcv_calldata = 0;
+        *nb_rcv_calls += 1;
+    } else if call.nb_rcv_calldata == call.nb_calldata {
+        // No call open, or the current one already complete
+        return Err(AppSW::WrongState);
+    } else if iter.len() > call.nb_calldata - call.nb_rcv_calldata {
+        return Err(AppSW::CalldataOverflow);
     }
-}
-
-fn set_calldata_deploy_account_v1(
-    data: &[u8],
-    constructor_calldata: &mut Vec<FieldElement>,
-) -> Result<(), SetCallError> {
-    let iter = data.chunks(FIELD_ELEMENT_SIZE);
     for d in iter {
-        constructor_calldata.push(d.into());
+        // Only the first calldata felts are needed to decode the call
+        if call.calldata.len() < MAX_CALLDATA {
+            call.calldata.push(d.into());
+        }
+        hasher.update(d.into());
+        call.nb_rcv_calldata += 1;
+    }
+    // Keep the call once fully received, by the command completing it
+    if call.nb_rcv_calldata == call.nb_calldata && calls.len() < MAX_CALLS {
+        calls.push(call.clone());
     }
     Ok(())
 }
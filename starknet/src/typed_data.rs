@@ -0,0 +1,817 @@
+use crate::{
+    crypto::{self, pedersen::PedersenHasher, poseidon::PoseidonHasher, HasherTrait},
+    status::AppSW,
+    types::{FieldElement, P, U256},
+};
+
+extern crate alloc;
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Maximum number of type definitions (structs and enums) in a message
+const MAX_DEFINITIONS: usize = 16;
+/// Maximum nesting level of structs, enums and arrays
+const MAX_DEPTH: usize = 8;
+/// Maximum length in bytes of a type definition or of a string value
+const MAX_CHUNKED_LENGTH: usize = 1024;
+/// Maximum number of message fields that can be reviewed on screen
+pub const MAX_DISPLAYED_FIELDS: usize = 32;
+
+/// Type name of the SNIP-12 revision 1 domain separator
+const DOMAIN_TYPE_NAME: &str = "StarknetDomain";
+/// Type name of the SNIP-12 revision 0 (legacy) domain separator
+const LEGACY_DOMAIN_TYPE_NAME: &str = "StarkNetDomain";
+/// Type encoding of the u256 SNIP-12 preset type
+const U256_TYPE_ENCODING: &str = "\"u256\"(\"low\":\"u128\",\"high\":\"u128\")";
+/// 'StarkNet Message' short string
+const STARKNET_MESSAGE: FieldElement = FieldElement {
+    value: [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x53, 0x74, 0x61, 0x72, 0x6b, 0x4e, 0x65, 0x74, 0x20, 0x4d, 0x65, 0x73, 0x73, 0x61,
+        0x67, 0x65,
+    ],
+};
+
+/// SNIP-12 revision, detected from the domain separator type name
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Revision {
+    /// Pedersen hashing, `StarkNetDomain`
+    V0,
+    /// Poseidon hashing, `StarknetDomain`
+    #[default]
+    V1,
+}
+
+impl Revision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Revision::V0 => "0 (legacy)",
+            Revision::V1 => "1",
+        }
+    }
+}
+
+/// Hash function of the revision: array hashes of revision 0 are Pedersen
+/// hashes on elements followed by their count (`compute_hash_on_elements`)
+#[derive(Debug)]
+enum Hasher {
+    Pedersen(PedersenHasher),
+    Poseidon(PoseidonHasher),
+}
+
+impl Hasher {
+    fn new(revision: Revision) -> Self {
+        match revision {
+            Revision::V0 => Hasher::Pedersen(PedersenHasher::default()),
+            Revision::V1 => Hasher::Poseidon(PoseidonHasher::default()),
+        }
+    }
+}
+
+impl HasherTrait for Hasher {
+    fn update(&mut self, data: FieldElement) {
+        match self {
+            Hasher::Pedersen(h) => h.update(data),
+            Hasher::Poseidon(h) => h.update(data),
+        }
+    }
+
+    fn finalize(self) -> FieldElement {
+        match self {
+            Hasher::Pedersen(mut h) => {
+                h.update(FieldElement::from(h.get_nb_fe()));
+                h.finalize()
+            }
+            Hasher::Poseidon(h) => h.finalize(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BasicType {
+    Felt,
+    ShortString,
+    U128,
+    U256,
+    ContractAddress,
+    ClassHash,
+    Timestamp,
+    Bool,
+    String,
+}
+
+impl BasicType {
+    fn from_name(name: &str, revision: Revision) -> Option<BasicType> {
+        match (name, revision) {
+            // Revision 0 strings are short strings
+            ("string", Revision::V0) => Some(BasicType::ShortString),
+            ("u256", Revision::V0) => None,
+            _ => Self::from_name_v1(name),
+        }
+    }
+
+    fn from_name_v1(name: &str) -> Option<BasicType> {
+        match name {
+            // Selectors are sent already hashed
+            "felt" | "selector" => Some(BasicType::Felt),
+            "shortstring" => Some(BasicType::ShortString),
+            "u128" => Some(BasicType::U128),
+            "u256" => Some(BasicType::U256),
+            "ContractAddress" => Some(BasicType::ContractAddress),
+            "ClassHash" => Some(BasicType::ClassHash),
+            "timestamp" => Some(BasicType::Timestamp),
+            "bool" => Some(BasicType::Bool),
+            "string" => Some(BasicType::String),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FieldType {
+    Basic(BasicType),
+    /// Index of a struct or enum in the received definitions
+    Custom(usize),
+    Array(Box<FieldType>),
+}
+
+/// Struct member, or enum variant
+#[derive(Debug, Default)]
+struct Member {
+    name: String,
+    type_name: String,
+    /// Resolved type of a struct member, or types of the enum variant parameters
+    types: Vec<FieldType>,
+}
+
+#[derive(Debug, Default)]
+struct TypeDefinition {
+    name: String,
+    is_enum: bool,
+    members: Vec<Member>,
+    type_hash: FieldElement,
+}
+
+#[derive(Debug)]
+enum Frame {
+    Struct {
+        def: usize,
+        next: usize,
+        hasher: Hasher,
+    },
+    Enum {
+        def: usize,
+        variant: Option<usize>,
+        next: usize,
+        hasher: Hasher,
+    },
+    Array {
+        elem: FieldType,
+        len: Option<usize>,
+        next: usize,
+        hasher: Hasher,
+    },
+}
+
+impl Frame {
+    fn absorb(&mut self, value: FieldElement) {
+        match self {
+            Frame::Struct { next, hasher, .. }
+            | Frame::Enum { next, hasher, .. }
+            | Frame::Array { next, hasher, .. } => {
+                hasher.update(value);
+                *next += 1;
+            }
+        }
+    }
+
+    fn finalize(self) -> FieldElement {
+        match self {
+            Frame::Struct { hasher, .. }
+            | Frame::Enum { hasher, .. }
+            | Frame::Array { hasher, .. } => hasher.finalize(),
+        }
+    }
+}
+
+/// What the walker expects to receive with the next value APDU
+enum Next {
+    Type(FieldType),
+    ArrayLength,
+    VariantIndex,
+}
+
+/// Buffer used to reassemble data spanning several APDUs.
+/// The first chunk starts with the total length encoded on 2 bytes.
+#[derive(Debug, Default)]
+struct ChunkBuffer {
+    len: usize,
+    data: Vec<u8>,
+}
+
+impl ChunkBuffer {
+    fn is_pending(&self) -> bool {
+        self.data.len() < self.len
+    }
+
+    fn push(&mut self, data: &[u8]) -> Result<Option<Vec<u8>>, AppSW> {
+        let data = match self.is_pending() {
+            true => data,
+            false => {
+                if data.len() < 2 {
+                    return Err(AppSW::TypedDataInvalidValue);
+                }
+                let len = u16::from_be_bytes([data[0], data[1]]) as usize;
+                if len > MAX_CHUNKED_LENGTH {
+                    return Err(AppSW::TypedDataInvalidValue);
+                }
+                self.len = len;
+                self.data = Vec::with_capacity(len);
+                &data[2..]
+            }
+        };
+        if self.data.len() + data.len() > self.len {
+            return Err(AppSW::TypedDataInvalidValue);
+        }
+        self.data.extend_from_slice(data);
+        match self.is_pending() {
+            true => Ok(None),
+            false => {
+                self.len = 0;
+                Ok(Some(core::mem::take(&mut self.data)))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TypedData {
+    pub account_address: FieldElement,
+    pub revision: Revision,
+    pub domain_hash: Option<FieldElement>,
+    /// Name, version and chain id of the domain, in received order
+    pub domain_fields: Vec<(String, String)>,
+    /// Message fields as (label, value); None if too many to be reviewed
+    pub fields: Option<Vec<(String, String)>>,
+    definitions: Vec<TypeDefinition>,
+    definition: ChunkBuffer,
+    string: ChunkBuffer,
+    stack: Vec<Frame>,
+}
+
+/// Store the address of the account signing the message
+pub fn set_account_address(data: &[u8], td: &mut TypedData) -> Result<(), AppSW> {
+    if data.len() != 32 {
+        return Err(AppSW::TypedDataInvalidValue);
+    }
+    td.account_address = data.into();
+    Ok(())
+}
+
+/// Receive a struct or enum definition:
+/// kind (1: 0 struct, 1 enum) | name length (1) | name | members count (1) |
+/// for each member: name length (1) | name | type length (1) | type
+pub fn add_definition(data: &[u8], td: &mut TypedData) -> Result<(), AppSW> {
+    if !td.stack.is_empty() || td.domain_hash.is_some() {
+        return Err(AppSW::TypedDataUnexpectedData);
+    }
+    let buf = match td.definition.push(data)? {
+        Some(buf) => buf,
+        None => return Ok(()),
+    };
+    if td.definitions.len() == MAX_DEFINITIONS {
+        return Err(AppSW::TypedDataInvalidDefinition);
+    }
+
+    let mut iter = buf.iter();
+    let mut def = TypeDefinition {
+        is_enum: match iter.next() {
+            Some(0) => false,
+            Some(1) => true,
+            _ => return Err(AppSW::TypedDataInvalidDefinition),
+        },
+        name: read_string(&mut iter)?,
+        ..Default::default()
+    };
+    let nb_members = *iter.next().ok_or(AppSW::TypedDataInvalidDefinition)?;
+    for _ in 0..nb_members {
+        def.members.push(Member {
+            name: read_string(&mut iter)?,
+            type_name: read_string(&mut iter)?,
+            ..Default::default()
+        });
+    }
+    if iter.next().is_some() || td.definitions.iter().any(|d| d.name == def.name) {
+        return Err(AppSW::TypedDataInvalidDefinition);
+    }
+    td.definitions.push(def);
+    Ok(())
+}
+
+/// Start encoding a struct: the domain separator first, then the message primary type
+pub fn start_struct(data: &[u8], td: &mut TypedData) -> Result<(), AppSW> {
+    if !td.stack.is_empty() || td.fields.is_some() || td.definition.is_pending() {
+        return Err(AppSW::TypedDataUnexpectedData);
+    }
+    let name = core::str::from_utf8(data).map_err(|_| AppSW::TypedDataUnknownType)?;
+
+    match td.domain_hash {
+        None => {
+            td.revision = match name {
+                DOMAIN_TYPE_NAME => Revision::V1,
+                LEGACY_DOMAIN_TYPE_NAME => Revision::V0,
+                _ => return Err(AppSW::TypedDataUnknownType),
+            };
+            resolve_definitions(td)?;
+        }
+        Some(_) => td.fields = Some(Vec::new()),
+    }
+
+    let def = find_definition(&td.definitions, name).ok_or(AppSW::TypedDataUnknownType)?;
+    if td.definitions[def].is_enum {
+        return Err(AppSW::TypedDataUnknownType);
+    }
+    push_frame(td, FieldType::Custom(def))?;
+    // Empty structs are complete right away
+    match settle(td)? {
+        Some(hash) => complete_struct(td, hash).map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+/// Receive the next value of the struct being encoded.
+/// Returns the message hash once the primary type has been fully received.
+pub fn set_value(data: &[u8], td: &mut TypedData) -> Result<Option<FieldElement>, AppSW> {
+    if td.stack.is_empty() {
+        return Err(AppSW::TypedDataUnexpectedData);
+    }
+
+    let next = match td.string.is_pending() {
+        true => Next::Type(FieldType::Basic(BasicType::String)),
+        false => top_next(td).ok_or(AppSW::TypedDataUnexpectedData)?,
+    };
+
+    match next {
+        Next::ArrayLength => {
+            let len = read_usize(data)?;
+            if let Some(Frame::Array { len: l, .. }) = td.stack.last_mut() {
+                *l = Some(len);
+            }
+        }
+        Next::VariantIndex => {
+            let index = read_usize(data)?;
+            let nb_params = match td.stack.last() {
+                Some(Frame::Enum { def, .. }) => match td.definitions[*def].members.get(index) {
+                    Some(variant) => variant.types.len(),
+                    None => return Err(AppSW::TypedDataInvalidValue),
+                },
+                _ => return Err(AppSW::TypedDataUnexpectedData),
+            };
+            if let Some(Frame::Enum {
+                variant, hasher, ..
+            }) = td.stack.last_mut()
+            {
+                *variant = Some(index);
+                hasher.update(FieldElement::from(index));
+                // Variants without parameters are encoded as (index, 0),
+                // matching the starknet.js reference implementation
+                if nb_params == 0 {
+                    hasher.update(FieldElement::ZERO);
+                }
+            }
+        }
+        Next::Type(FieldType::Basic(ty)) => {
+            let (value, display) = match ty {
+                BasicType::String => match td.string.push(data)? {
+                    Some(bytes) => encode_string(&bytes),
+                    None => return Ok(None),
+                },
+                BasicType::U256 => encode_u256(data)?,
+                _ => encode_basic(ty, data)?,
+            };
+            // Domain values are short strings by convention, even when typed as felts
+            let display = match (ty, td.domain_hash) {
+                (BasicType::Felt, None) => shortstring_to_string(&value),
+                _ => display,
+            };
+            let label = field_label(td);
+            match td.domain_hash {
+                None => td.domain_fields.push((label, display)),
+                Some(_) => {
+                    if let Some(fields) = &mut td.fields {
+                        match fields.len() < MAX_DISPLAYED_FIELDS {
+                            true => fields.push((label, display)),
+                            false => td.fields = None,
+                        }
+                    }
+                }
+            }
+            td.stack.last_mut().unwrap().absorb(value);
+        }
+        Next::Type(_) => return Err(AppSW::TypedDataUnexpectedData),
+    }
+
+    match settle(td)? {
+        Some(hash) => complete_struct(td, hash),
+        None => Ok(None),
+    }
+}
+
+fn complete_struct(td: &mut TypedData, hash: FieldElement) -> Result<Option<FieldElement>, AppSW> {
+    match td.domain_hash {
+        None => {
+            td.domain_hash = Some(hash);
+            Ok(None)
+        }
+        Some(domain_hash) => {
+            let mut hasher = Hasher::new(td.revision);
+            hasher.update(STARKNET_MESSAGE);
+            hasher.update(domain_hash);
+            hasher.update(td.account_address);
+            hasher.update(hash);
+            Ok(Some(hasher.finalize()))
+        }
+    }
+}
+
+/// What the innermost frame expects next, None if it is complete
+fn top_next(td: &TypedData) -> Option<Next> {
+    match td.stack.last()? {
+        Frame::Struct { def, next, .. } => td.definitions[*def]
+            .members
+            .get(*next)
+            .map(|m| Next::Type(m.types[0].clone())),
+        Frame::Enum { variant: None, .. } => Some(Next::VariantIndex),
+        Frame::Enum {
+            def,
+            variant: Some(v),
+            next,
+            ..
+        } => td.definitions[*def].members[*v]
+            .types
+            .get(*next)
+            .map(|t| Next::Type(t.clone())),
+        Frame::Array { len: None, .. } => Some(Next::ArrayLength),
+        Frame::Array {
+            elem,
+            len: Some(len),
+            next,
+            ..
+        } => match next < len {
+            true => Some(Next::Type(elem.clone())),
+            false => None,
+        },
+    }
+}
+
+/// Pop completed frames and open frames for nested types until a value is expected.
+/// Returns the hash of the root struct once it is complete.
+fn settle(td: &mut TypedData) -> Result<Option<FieldElement>, AppSW> {
+    loop {
+        match top_next(td) {
+            None => {
+                let hash = td.stack.pop().unwrap().finalize();
+                match td.stack.last_mut() {
+                    Some(frame) => frame.absorb(hash),
+                    None => return Ok(Some(hash)),
+                }
+            }
+            Some(Next::Type(ty @ (FieldType::Custom(_) | FieldType::Array(_)))) => {
+                push_frame(td, ty)?;
+            }
+            Some(_) => return Ok(None),
+        }
+    }
+}
+
+fn push_frame(td: &mut TypedData, ty: FieldType) -> Result<(), AppSW> {
+    if td.stack.len() == MAX_DEPTH {
+        return Err(AppSW::TypedDataInvalidValue);
+    }
+    let frame = match ty {
+        FieldType::Custom(def) => match td.definitions[def].is_enum {
+            true => Frame::Enum {
+                def,
+                variant: None,
+                next: 0,
+                hasher: Hasher::new(td.revision),
+            },
+            false => {
+                let mut hasher = Hasher::new(td.revision);
+                hasher.update(td.definitions[def].type_hash);
+                Frame::Struct {
+                    def,
+                    next: 0,
+                    hasher,
+                }
+            }
+        },
+        FieldType::Array(elem) => Frame::Array {
+            elem: *elem,
+            len: None,
+            next: 0,
+            hasher: Hasher::new(td.revision),
+        },
+        FieldType::Basic(_) => return Err(AppSW::TypedDataUnexpectedData),
+    };
+    td.stack.push(frame);
+    Ok(())
+}
+
+/// Label of the value about to be received, e.g. "to.wallets[1]"
+fn field_label(td: &TypedData) -> String {
+    let mut label = String::new();
+    // The root struct name is not part of the label
+    for frame in td.stack.iter() {
+        match frame {
+            Frame::Struct { def, next, .. } => {
+                if !label.is_empty() {
+                    label.push('.');
+                }
+                label.push_str(&td.definitions[*def].members[*next].name);
+            }
+            Frame::Enum {
+                def,
+                variant: Some(v),
+                next,
+                ..
+            } => {
+                let variant = &td.definitions[*def].members[*v];
+                label.push('.');
+                label.push_str(&variant.name);
+                if variant.types.len() > 1 {
+                    label.push_str(&format!("[{}]", next));
+                }
+            }
+            Frame::Array { next, .. } => label.push_str(&format!("[{}]", next)),
+            Frame::Enum { variant: None, .. } => (),
+        }
+    }
+    label
+}
+
+/// Resolve member types of all definitions and compute their type hashes
+fn resolve_definitions(td: &mut TypedData) -> Result<(), AppSW> {
+    let revision = td.revision;
+    for i in 0..td.definitions.len() {
+        // Enums were introduced with revision 1
+        if td.definitions[i].is_enum && revision == Revision::V0 {
+            return Err(AppSW::TypedDataInvalidDefinition);
+        }
+        for j in 0..td.definitions[i].members.len() {
+            let type_name = &td.definitions[i].members[j].type_name;
+            let types = match td.definitions[i].is_enum {
+                false => Vec::from([parse_type(&td.definitions, type_name, revision)?]),
+                true => {
+                    let params = type_name
+                        .strip_prefix('(')
+                        .and_then(|s| s.strip_suffix(')'))
+                        .ok_or(AppSW::TypedDataInvalidDefinition)?;
+                    let mut types = Vec::new();
+                    for param in params.split(',').filter(|p| !p.is_empty()) {
+                        types.push(parse_type(&td.definitions, param, revision)?);
+                    }
+                    types
+                }
+            };
+            td.definitions[i].members[j].types = types;
+        }
+    }
+    for i in 0..td.definitions.len() {
+        let encoding = encode_type(&td.definitions, i, revision);
+        td.definitions[i].type_hash = crypto::starknet_keccak(encoding.as_bytes());
+    }
+    Ok(())
+}
+
+fn find_definition(definitions: &[TypeDefinition], name: &str) -> Option<usize> {
+    definitions.iter().position(|d| d.name == name)
+}
+
+fn parse_type(
+    definitions: &[TypeDefinition],
+    name: &str,
+    revision: Revision,
+) -> Result<FieldType, AppSW> {
+    if let Some(elem) = name.strip_suffix('*') {
+        return Ok(FieldType::Array(Box::new(parse_type(
+            definitions,
+            elem,
+            revision,
+        )?)));
+    }
+    if let Some(basic) = BasicType::from_name(name, revision) {
+        return Ok(FieldType::Basic(basic));
+    }
+    match find_definition(definitions, name) {
+        Some(idx) => Ok(FieldType::Custom(idx)),
+        None => Err(AppSW::TypedDataUnknownType),
+    }
+}
+
+/// SNIP-12 encoding of a type: the type itself followed by all the
+/// types it references, sorted by name
+fn encode_type(definitions: &[TypeDefinition], idx: usize, revision: Revision) -> String {
+    let mut deps: Vec<usize> = Vec::new();
+    let mut has_u256 = false;
+    for member in definitions[idx].members.iter() {
+        for ty in member.types.iter() {
+            collect_dependencies(definitions, idx, ty, &mut deps, &mut has_u256);
+        }
+    }
+
+    let mut encodings: Vec<(&str, String)> = deps
+        .iter()
+        .map(|&d| {
+            (
+                definitions[d].name.as_str(),
+                encode_single_type(&definitions[d], revision),
+            )
+        })
+        .collect();
+    if has_u256 {
+        encodings.push(("u256", U256_TYPE_ENCODING.to_string()));
+    }
+    encodings.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut res = encode_single_type(&definitions[idx], revision);
+    for (_, encoding) in encodings {
+        res.push_str(&encoding);
+    }
+    res
+}
+
+fn collect_dependencies(
+    definitions: &[TypeDefinition],
+    primary: usize,
+    ty: &FieldType,
+    deps: &mut Vec<usize>,
+    has_u256: &mut bool,
+) {
+    match ty {
+        FieldType::Basic(BasicType::U256) => *has_u256 = true,
+        FieldType::Basic(_) => (),
+        FieldType::Array(elem) => collect_dependencies(definitions, primary, elem, deps, has_u256),
+        FieldType::Custom(idx) => {
+            if *idx != primary && !deps.contains(idx) {
+                deps.push(*idx);
+                for member in definitions[*idx].members.iter() {
+                    for ty in member.types.iter() {
+                        collect_dependencies(definitions, primary, ty, deps, has_u256);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn encode_single_type(def: &TypeDefinition, revision: Revision) -> String {
+    // Revision 0 names are not quoted: "Name(member:type,...)"
+    if revision == Revision::V0 {
+        let members: Vec<String> = def
+            .members
+            .iter()
+            .map(|m| format!("{}:{}", m.name, m.type_name))
+            .collect();
+        return format!("{}({})", def.name, members.join(","));
+    }
+    let members: Vec<String> = def
+        .members
+        .iter()
+        .map(|m| match def.is_enum {
+            false => format!("\"{}\":\"{}\"", m.name, m.type_name),
+            true => {
+                let params: Vec<String> = m.type_name[1..m.type_name.len() - 1]
+                    .split(',')
+                    .filter(|p| !p.is_empty())
+                    .map(|p| format!("\"{}\"", p))
+                    .collect();
+                format!("\"{}\":({})", m.name, params.join(","))
+            }
+        })
+        .collect();
+    format!("\"{}\"({})", def.name, members.join(","))
+}
+
+fn read_string(iter: &mut core::slice::Iter<u8>) -> Result<String, AppSW> {
+    let len = *iter.next().ok_or(AppSW::TypedDataInvalidDefinition)? as usize;
+    let bytes = iter.as_slice();
+    if bytes.len() < len {
+        return Err(AppSW::TypedDataInvalidDefinition);
+    }
+    let s = core::str::from_utf8(&bytes[..len]).map_err(|_| AppSW::TypedDataInvalidDefinition)?;
+    if len > 0 {
+        iter.nth(len - 1);
+    }
+    Ok(s.to_string())
+}
+
+fn read_felt(data: &[u8]) -> Result<FieldElement, AppSW> {
+    if data.is_empty() || data.len() > 32 {
+        return Err(AppSW::TypedDataInvalidValue);
+    }
+    let value = FieldElement::from(data);
+    match value < P {
+        true => Ok(value),
+        false => Err(AppSW::TypedDataInvalidValue),
+    }
+}
+
+fn read_usize(data: &[u8]) -> Result<usize, AppSW> {
+    let value = read_felt(data)?;
+    match value.value[..28].iter().all(|&b| b == 0) {
+        true => usize::try_from(value),
+        false => Err(AppSW::TypedDataInvalidValue),
+    }
+}
+
+fn encode_basic(ty: BasicType, data: &[u8]) -> Result<(FieldElement, String), AppSW> {
+    let value = read_felt(data)?;
+    let display = match ty {
+        BasicType::Felt => felt_to_string(&value),
+        BasicType::ShortString => {
+            if value.value[0] != 0 {
+                return Err(AppSW::TypedDataInvalidValue);
+            }
+            shortstring_to_string(&value)
+        }
+        BasicType::U128 | BasicType::Timestamp => {
+            if value.value[..16].iter().any(|&b| b != 0) {
+                return Err(AppSW::TypedDataInvalidValue);
+            }
+            value.to_dec_string(None)
+        }
+        BasicType::ContractAddress | BasicType::ClassHash => {
+            let mut s = value.to_hex_string();
+            s.insert_str(0, "0x");
+            s
+        }
+        BasicType::Bool => match value {
+            FieldElement::ZERO => "false".to_string(),
+            FieldElement::ONE => "true".to_string(),
+            _ => return Err(AppSW::TypedDataInvalidValue),
+        },
+        BasicType::U256 | BasicType::String => return Err(AppSW::TypedDataUnexpectedData),
+    };
+    Ok((value, display))
+}
+
+/// u256 values are received as 32-byte big-endian integers and encoded as the
+/// (low, high) u256 preset struct
+fn encode_u256(data: &[u8]) -> Result<(FieldElement, String), AppSW> {
+    if data.is_empty() || data.len() > 32 {
+        return Err(AppSW::TypedDataInvalidValue);
+    }
+    let value = U256::from(data);
+    let mut hasher = PoseidonHasher::default();
+    hasher.update(crypto::starknet_keccak(U256_TYPE_ENCODING.as_bytes()));
+    hasher.update(value.low);
+    hasher.update(value.high);
+    Ok((hasher.finalize(), value.to_dec_string(None)))
+}
+
+/// Strings are encoded as a Cairo ByteArray: number of full 31-byte words,
+/// the full words, the pending word and its length
+fn encode_string(bytes: &[u8]) -> (FieldElement, String) {
+    let mut hasher = PoseidonHasher::default();
+    let words = bytes.chunks_exact(31);
+    let pending = words.remainder();
+    hasher.update(FieldElement::from(words.len()));
+    for word in words {
+        hasher.update(FieldElement::from(word));
+    }
+    hasher.update(FieldElement::from(pending));
+    hasher.update(FieldElement::from(pending.len()));
+    (
+        hasher.finalize(),
+        String::from_utf8_lossy(bytes).to_string(),
+    )
+}
+
+fn felt_to_string(value: &FieldElement) -> String {
+    let s = value.to_hex_string();
+    let mut s = String::from(s.trim_start_matches('0'));
+    if s.is_empty() {
+        s.push('0');
+    }
+    s.insert_str(0, "0x");
+    s
+}
+
+/// Printable short strings are shown as text, other values (e.g. a `1` revision) in decimal
+fn shortstring_to_string(value: &FieldElement) -> String {
+    let bytes: Vec<u8> = value
+        .value
+        .iter()
+        .copied()
+        .skip_while(|&b| b == 0)
+        .collect();
+    match !bytes.is_empty() && bytes.iter().all(|b| (0x20..0x7f).contains(b)) {
+        true => String::from_utf8_lossy(&bytes).to_string(),
+        false => value.to_dec_string(None),
+    }
+}
@@ -1,6 +1,8 @@
 extern crate alloc;
+use crate::status::AppSW;
 use alloc::string::{String, ToString};
 use core::ops::{Add, AddAssign, Div, Mul, Rem, Sub};
+use ledger_device_sdk::hash::{sha3::Keccak256, HashInit};
 use ledger_secure_sdk_sys::*;
 use num_bigint::BigUint;
 
@@ -26,6 +28,41 @@ impl FieldElement {
         ],
     };
 
+    pub const DECLARE: FieldElement = FieldElement {
+        value: [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x64, 0x65, 0x63,
+            0x6c, 0x61, 0x72, 0x65,
+        ],
+    };
+
+    /// Prefix of the contract address preimage, "STARKNET_CONTRACT_ADDRESS"
+    pub const CONTRACT_ADDRESS_PREFIX: FieldElement = FieldElement {
+        value: [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x53, 0x54, 0x41, 0x52, 0x4b, 0x4e, 0x45,
+            0x54, 0x5f, 0x43, 0x4f, 0x4e, 0x54, 0x52, 0x41, 0x43, 0x54, 0x5f, 0x41, 0x44, 0x44,
+            0x52, 0x45, 0x53, 0x53,
+        ],
+    };
+
+    /// Chain id of Starknet Mainnet
+    pub const SN_MAIN: FieldElement = FieldElement {
+        value: [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x53, 0x4e, 0x5f,
+            0x4d, 0x41, 0x49, 0x4e,
+        ],
+    };
+
+    /// Chain id of Starknet Sepolia
+    pub const SN_SEPOLIA: FieldElement = FieldElement {
+        value: [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x53, 0x4e, 0x5f, 0x53, 0x45, 0x50,
+            0x4f, 0x4c, 0x49, 0x41,
+        ],
+    };
+
     pub const ZERO: FieldElement = FieldElement { value: [0u8; 32] };
 
     pub const ONE: FieldElement = FieldElement {
@@ -52,6 +89,24 @@ impl FieldElement {
         ],
     };
 
+    /// 2^128 - 1, the largest value of each half of a u256
+    pub const U128_MAX: FieldElement = FieldElement {
+        value: [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff,
+        ],
+    };
+
+    /// 2^251 - 256, contract addresses are reduced modulo this bound
+    pub const ADDR_BOUND: FieldElement = FieldElement {
+        value: [
+            0x07, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0x00,
+        ],
+    };
+
     pub fn new() -> Self {
         Self::default()
     }
@@ -88,36 +143,118 @@ impl FieldElement {
 
     #[allow(clippy::wrong_self_convention)]
     pub fn to_dec_string(&self, decimals: Option<usize>) -> String {
-        let bn = BigUint::from_bytes_be(self.value.as_ref());
-        if bn == BigUint::ZERO {
-            return "0".to_string();
+        to_dec_string(BigUint::from_bytes_be(self.value.as_ref()), decimals)
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_hex_string(&self) -> String {
+        hex::encode(self.value)
+    }
+
+    /// Checksummed address: 0x and 64 hex digits, a letter being uppercase when the
+    /// matching nibble of the Keccak-256 of the address minimal big-endian bytes is >= 8
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_checksum_string(&self) -> String {
+        let start = self.value.iter().position(|b| *b != 0).unwrap_or(31);
+        let mut hash = [0u8; 32];
+        let mut keccak = Keccak256::new();
+        keccak.hash(&self.value[start..], &mut hash).unwrap();
+
+        let mut s = String::from("0x");
+        for (i, c) in self.to_hex_string().chars().enumerate() {
+            let nibble = match i % 2 {
+                0 => hash[i / 2] >> 4,
+                _ => hash[i / 2] & 0x0f,
+            };
+            match nibble >= 8 {
+                true => s.push(c.to_ascii_uppercase()),
+                false => s.push(c),
+            }
         }
-        match decimals {
-            Some(d) => {
-                let mut bn_str = bn.to_string();
-                let len = bn_str.len();
-                if len <= d {
-                    bn_str = bn_str.trim_end_matches('0').to_string();
-                    let mut s = String::from("0.");
-                    s.push_str(&"0".repeat(d - len));
-                    s.push_str(&bn_str);
-                    s
-                } else {
-                    let (int_part, dec_part) = bn_str.split_at(len - d);
-                    let mut s = String::from(int_part);
+        s
+    }
+}
+
+/// Decimal representation of a number, as a token amount with
+/// `decimals` decimals if given
+pub fn to_dec_string(bn: BigUint, decimals: Option<usize>) -> String {
+    if bn == BigUint::ZERO {
+        return "0".to_string();
+    }
+    match decimals {
+        Some(d) => {
+            let mut bn_str = bn.to_string();
+            let len = bn_str.len();
+            if len <= d {
+                bn_str = bn_str.trim_end_matches('0').to_string();
+                let mut s = String::from("0.");
+                s.push_str(&"0".repeat(d - len));
+                s.push_str(&bn_str);
+                s
+            } else {
+                let (int_part, dec_part) = bn_str.split_at(len - d);
+                let mut s = String::from(int_part);
+                let dec_part = dec_part.trim_end_matches('0');
+                if !dec_part.is_empty() {
                     s.push('.');
-                    let dec_part = dec_part.trim_end_matches('0');
                     s.push_str(dec_part);
-                    s
                 }
+                s
             }
-            None => bn.to_string(),
         }
+        None => bn.to_string(),
     }
+}
+
+/// Cairo u256, made of two 128-bit limbs each serialized as a felt
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct U256 {
+    pub low: FieldElement,
+    pub high: FieldElement,
+}
 
+impl U256 {
+    pub const MAX: U256 = U256 {
+        low: FieldElement::U128_MAX,
+        high: FieldElement::U128_MAX,
+    };
+
+    /// Builds a u256 from its limbs, None if one of them does not fit on 128 bits
+    pub fn from_limbs(low: FieldElement, high: FieldElement) -> Option<Self> {
+        match low <= FieldElement::U128_MAX && high <= FieldElement::U128_MAX {
+            true => Some(Self { low, high }),
+            false => None,
+        }
+    }
+
+    pub fn is_max(&self) -> bool {
+        *self == U256::MAX
+    }
+
+    /// 32-byte big-endian representation
     #[allow(clippy::wrong_self_convention)]
-    pub fn to_hex_string(&self) -> String {
-        hex::encode(self.value)
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[..16].copy_from_slice(&self.high.value[16..]);
+        bytes[16..].copy_from_slice(&self.low.value[16..]);
+        bytes
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_dec_string(&self, decimals: Option<usize>) -> String {
+        to_dec_string(BigUint::from_bytes_be(&self.to_be_bytes()), decimals)
+    }
+}
+
+/// Builds a u256 from a big-endian integer of at most 32 bytes
+impl From<&[u8]> for U256 {
+    fn from(data: &[u8]) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[32 - data.len()..].copy_from_slice(data);
+        Self {
+            low: FieldElement::from(&bytes[16..]),
+            high: FieldElement::from(&bytes[..16]),
+        }
     }
 }
 
@@ -285,8 +422,10 @@ impl From<usize> for FieldElement {
     }
 }
 
-impl From<FieldElement> for usize {
-    fn from(fe: FieldElement) -> usize {
+/// Counts and lengths sent by the host, values over usize::MAX being rejected
+impl TryFrom<FieldElement> for usize {
+    type Error = AppSW;
+    fn try_from(fe: FieldElement) -> Result<usize, Self::Error> {
         let mut value: usize = 0;
         let size_of_usize = core::mem::size_of::<usize>();
         let offset = if size_of_usize >= fe.value.len() {
@@ -294,12 +433,15 @@ impl From<FieldElement> for usize {
         } else {
             fe.value.len() - size_of_usize
         };
+        if fe.value[..offset].iter().any(|b| *b != 0) {
+            return Err(AppSW::InvalidData);
+        }
 
         for i in 0..size_of_usize {
             value |= (fe.value[i + offset] as usize) << ((size_of_usize - 1 - i) * 8);
         }
 
-        value
+        Ok(value)
     }
 }
 
@@ -0,0 +1,37 @@
+from typing import List, Tuple
+
+from cryptography.hazmat.primitives import hashes
+from cryptography.hazmat.primitives.asymmetric import ec
+
+from application_client.token_info import TEST_TOKEN_INFO_PRIVATE_KEY
+
+FELT             = 0x00
+CONTRACT_ADDRESS = 0x01
+U256             = 0x02
+TOKEN_AMOUNT     = 0x03
+# Token reference of a TOKEN_AMOUNT parameter designating the called contract
+CALLED_CONTRACT  = 0xFF
+
+# Build the PROVIDE_CALL_DESCRIPTOR payloads, as (P1, payload):
+# P1 = 0: address (32) | selector (32) | intent_len (1) | intent
+# P1 = 1: one per parameter: name_len (1) | name | type (1) | token reference (1, token amounts only)
+# P1 = 2: signature (DER) of the SHA-256 of 0x02 (call descriptor kind) followed by the previous payloads
+def call_descriptor_payloads(address: str,
+                             selector: str,
+                             intent: str,
+                             params: List[Tuple[str, int, int]],
+                             private_key: int = TEST_TOKEN_INFO_PRIVATE_KEY) -> List[Tuple[int, bytes]]:
+    payloads = []
+    start = bytes.fromhex(address.removeprefix("0x").zfill(64))
+    start += bytes.fromhex(selector.removeprefix("0x").zfill(64))
+    start += bytes([len(intent)]) + intent.encode("ascii")
+    payloads.append((0x00, start))
+    for name, kind, token_ref in params:
+        param = bytes([len(name)]) + name.encode("ascii") + bytes([kind])
+        if kind == TOKEN_AMOUNT:
+            param += bytes([token_ref])
+        payloads.append((0x01, param))
+    message = bytes([0x02]) + b"".join(p for _, p in payloads)
+    key = ec.derive_private_key(private_key, ec.SECP256K1())
+    payloads.append((0x02, key.sign(message, ec.ECDSA(hashes.SHA256()))))
+    return payloads
@@ -90,6 +90,7 @@ class Errors(IntEnum):
     SW_INS_NOT_SUPPORTED       = 0x6E01
     SW_WRONG_P1P2              = 0x6E02
     SW_WRONG_APDU_LENGTH       = 0x6E03
+    SW_USER_CANCELLED          = 0x6E04
     SW_WRONG_RESPONSE_LENGTH   = 0xB000
     SW_DISPLAY_BIP32_PATH_FAIL = 0xB001
     SW_DISPLAY_ADDRESS_FAIL    = 0xB002
@@ -98,4 +99,31 @@ class Errors(IntEnum):
     SW_TX_PARSING_FAIL         = 0xB005
     SW_TX_HASH_FAIL            = 0xB006
     SW_BAD_STATE               = 0xB007
-    SW_SIGNATURE_FAIL          = 0xB008
\ No newline at end of file
+    SW_SIGNATURE_FAIL          = 0xB008
+    SW_INVALID_PATH_PREFIX = 0xFF00
+    SW_INVALID_PATH_LENGTH = 0xFF01
+    SW_SIGN_FAILED = 0xFF02
+    SW_TYPED_DATA_INVALID_DEFINITION = 0xFF10
+    SW_TYPED_DATA_UNKNOWN_TYPE       = 0xFF11
+    SW_TYPED_DATA_INVALID_VALUE      = 0xFF12
+    SW_TYPED_DATA_UNEXPECTED_DATA    = 0xFF13
+    SW_TOKEN_INFO_INVALID_DATA      = 0xFF20
+    SW_TOKEN_INFO_INVALID_SIGNATURE = 0xFF21
+    SW_CALL_DESCRIPTOR_INVALID_DATA      = 0xFF30
+    SW_CALL_DESCRIPTOR_INVALID_SIGNATURE = 0xFF31
+    SW_CALL_DESCRIPTOR_UNEXPECTED_DATA   = 0xFF32
+    SW_TOO_MUCH_DATA = 0xFF40
+    SW_INVALID_TIP = 0xFF50
+    SW_INVALID_RESOURCE_BOUNDS = 0xFF51
+    SW_UNKNOWN_DA_MODE = 0xFF60
+    SW_INVALID_TX_LENGTH = 0xFF70
+    SW_UNEXPECTED_TX_FIELD = 0xFF71
+    SW_UNSUPPORTED_TX_VERSION = 0xFF72
+    SW_INVALID_TX_STEP = 0xFF73
+    SW_CALLDATA_OVERFLOW = 0xFF74
+    SW_TOO_MANY_CALLS = 0xFF75
+    SW_INVALID_DATA = 0xFF76
+    SW_WRONG_TX_STATE = 0xFF80
+    SW_BLIND_SIGNING_DISABLED = 0xFF90
+    SW_UNKNOWN_CHAIN = 0xFF91
+    SW_FEE_CAP_EXCEEDED = 0xFF92
@@ -0,0 +1,21 @@
+from cryptography.hazmat.primitives import hashes
+from cryptography.hazmat.primitives.asymmetric import ec
+
+# Private part of the test key trusted by the app when built with the `trusted_test_key` feature
+TEST_TOKEN_INFO_PRIVATE_KEY = 0x68c93a6a36f2f83d355aa338d08f7caee51338d19e4179f8e14adb29241f38e5
+
+# Build a PROVIDE_TOKEN_INFO payload:
+# payload = address (32)
+#           decimals (1)
+#           ticker_len (1)
+#           ticker (var)
+#           signature (DER) of the SHA-256 of 0x01 (token info kind) followed by the previous fields
+def token_info_payload(address: str,
+                       ticker: str,
+                       decimals: int,
+                       private_key: int = TEST_TOKEN_INFO_PRIVATE_KEY) -> bytes:
+    message = bytes.fromhex(address.removeprefix("0x").zfill(64))
+    message += bytes([decimals, len(ticker)])
+    message += ticker.encode("ascii")
+    key = ec.derive_private_key(private_key, ec.SECP256K1())
+    return message + key.sign(bytes([0x01]) + message, ec.ECDSA(hashes.SHA256()))
@@ -0,0 +1,49 @@
+import pytest
+
+from application_client.response_unpacker import Errors
+from ragger.error import ExceptionRAPDU
+from ragger.navigator import NavInsID
+from utils import ROOT_SCREENSHOT_PATH
+
+PATH = "80000a55c741e9c9c47a6028800000008000000000000000"
+
+# Addresses of the accounts owned by the public key of PATH
+REF_ADDRESSES = {
+    0x00: "026803c0bac95b7c230343ffe265688bf2f57e508c4016b42110c5b58a6eec21", # OpenZeppelin
+    0x01: "0552b7af4d776b46d1530b23d0d28771616941a08cf61f991bdab19fdaa8d432", # Argent
+    0x02: "038315414a443eab296142fb28e52b563611937f877cb85fb19f6cb2e06178cc", # Braavos
+}
+
+# In this test we check that the GET_ACCOUNT_ADDRESS works in non-confirmation mode
+@pytest.mark.parametrize("account", REF_ADDRESSES.keys())
+def test_get_account_address_no_confirm(backend, account):
+    response = backend.exchange_raw(bytes.fromhex(f"5a0c00{account:02x}18{PATH}")).data
+    assert response == bytes.fromhex(REF_ADDRESSES[account])
+
+
+# In this test we check that the GET_ACCOUNT_ADDRESS works in confirmation mode
+def test_get_account_address_confirm_accepted(firmware, backend, navigator, test_name):
+    with backend.exchange_async_raw(bytes.fromhex(f"5a0c010118{PATH}")):
+        if firmware.device.startswith("nano"):
+            navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                      [NavInsID.BOTH_CLICK],
+                                                      "Approve",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+        else:
+            instructions = [
+                NavInsID.USE_CASE_CHOICE_CONFIRM,
+                NavInsID.USE_CASE_STATUS_DISMISS
+            ]
+            navigator.navigate_and_compare(ROOT_SCREENSHOT_PATH,
+                                           test_name,
+                                           instructions)
+    response = backend.last_async_response.data
+    assert response == bytes.fromhex(REF_ADDRESSES[0x01])
+
+
+# In this test we check that the GET_ACCOUNT_ADDRESS replies an error for an unknown account
+def test_get_account_address_unknown_account(backend):
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange_raw(bytes.fromhex(f"5a0c000318{PATH}"))
+    assert e.value.status == Errors.SW_WRONG_P1P2
@@ -2,33 +2,39 @@ import pytest
 
 from application_client.response_unpacker import unpack_get_public_key_response, unpack_sign_tx_response, Errors
 from ragger.navigator import NavInsID, NavIns
-from utils import ROOT_SCREENSHOT_PATH, read_lines_from_file, call_external_binary
+from utils import ROOT_SCREENSHOT_PATH, read_lines_from_file, call_external_binary, get_setting_position
 from ragger.firmware import Firmware
 
 CHECK_SIGNATURE_BINARY_PATH = "tools/check-signature/target/debug/check-signature"
 
-def get_setting_position(firmware: Firmware, setting_idx: int, per_page: int) -> tuple[int, int]:
-    if firmware == Firmware.STAX:
-        screen_height = 672  # px
-        screen_width = 400  # px
-        header_height = 88  # px
-        footer_height = 92  # px
-    elif firmware == Firmware.FLEX:
-        screen_height = 600  # px
-        screen_width = 480  # px
-        header_height = 96  # px
-        footer_height = 96  # px
-    elif firmware == Firmware.APEX_P:
-        screen_height = 400  # px
-        screen_width = 300  # px
-        header_height = 60  # px
-        footer_height = 96  # px
-
-    index_in_page = setting_idx % per_page
-    usable_height = screen_height - (header_height + footer_height)
-    setting_height = usable_height // per_page
-    offset = (setting_height * index_in_page) + (setting_height // 2)
-    return screen_width // 2, header_height + offset
+# Accept the blind signing warning, then go through the calls and hash up to the signature.
+# The review intro screen ends the first navigation and starts the second one.
+def navigate_blind_review(firmware, navigator, test_name):
+    if firmware.device.startswith("nano"):
+        navigator.navigate_and_compare(ROOT_SCREENSHOT_PATH,
+                                       test_name,
+                                       [NavInsID.RIGHT_CLICK, NavInsID.BOTH_CLICK])
+        navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                  [NavInsID.BOTH_CLICK],
+                                                  "Approve",
+                                                  ROOT_SCREENSHOT_PATH,
+                                                  test_name,
+                                                  screen_change_before_first_instruction=False,
+                                                  snap_start_idx=2)
+    else:
+        navigator.navigate_and_compare(ROOT_SCREENSHOT_PATH,
+                                       test_name,
+                                       [NavInsID.CENTERED_FOOTER_TAP])
+        navigator.navigate_until_text_and_compare(NavInsID.SWIPE_CENTER_TO_LEFT,
+                                                  [
+                                                      NavInsID.USE_CASE_REVIEW_CONFIRM,
+                                                      NavInsID.USE_CASE_STATUS_DISMISS
+                                                  ],
+                                                  "Hold to sign",
+                                                  ROOT_SCREENSHOT_PATH,
+                                                  test_name,
+                                                  screen_change_before_first_instruction=False,
+                                                  snap_start_idx=1)
 
 # In those tests we check the behavior of the device when asked to sign a Tx (blind signing)
 
@@ -70,36 +76,8 @@ def test_tx_v1_approve_and_remove(firmware, backend, navigator, test_name):
 
     # send last apdu and yield the response
     with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
-        if firmware.device.startswith("nano"):
-                navigator.navigate_until_text_and_compare(
-                    NavIns(NavInsID.WAIT, (0,)),
-                    [
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.BOTH_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.BOTH_CLICK
-                    ],
-                    "Blind",
-                    path=ROOT_SCREENSHOT_PATH,
-                    test_case_name=test_name
-                )
-        else:
-            navigator.navigate_until_text_and_compare(
-                NavIns(NavInsID.WAIT, (0,)),
-                [
-                    NavInsID.CENTERED_FOOTER_TAP,
-                    NavInsID.SWIPE_CENTER_TO_LEFT,
-                    NavInsID.SWIPE_CENTER_TO_LEFT,
-                    NavInsID.USE_CASE_REVIEW_CONFIRM,
-                    NavInsID.USE_CASE_STATUS_DISMISS
-                ],
-                "Blind signing ahead",
-                path=ROOT_SCREENSHOT_PATH,
-                test_case_name=test_name
-            )
-    
+        navigate_blind_review(firmware, navigator, test_name)
+
     response = backend.last_async_response.data
     
     hash, r, s, _ = unpack_sign_tx_response(response)
@@ -160,36 +138,8 @@ def test_tx_v1_approve_and_swap(firmware, backend, navigator, test_name):
 
     # send last apdu and yield the response
     with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
-        if firmware.device.startswith("nano"):
-                navigator.navigate_until_text_and_compare(
-                    NavIns(NavInsID.WAIT, (0,)),
-                    [
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.BOTH_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.BOTH_CLICK
-                    ],
-                    "Blind",
-                    path=ROOT_SCREENSHOT_PATH,
-                    test_case_name=test_name
-                )
-        else:
-            navigator.navigate_until_text_and_compare(
-                NavIns(NavInsID.WAIT, (0,)),
-                [
-                    NavInsID.CENTERED_FOOTER_TAP,
-                    NavInsID.SWIPE_CENTER_TO_LEFT,
-                    NavInsID.SWIPE_CENTER_TO_LEFT,
-                    NavInsID.USE_CASE_REVIEW_CONFIRM,
-                    NavInsID.USE_CASE_STATUS_DISMISS
-                ],
-                "Blind signing ahead",
-                path=ROOT_SCREENSHOT_PATH,
-                test_case_name=test_name
-            )
-    
+        navigate_blind_review(firmware, navigator, test_name)
+
     response = backend.last_async_response.data
     
     hash, r, s, _ = unpack_sign_tx_response(response)
@@ -250,36 +200,8 @@ def test_tx_v1_explore(firmware, backend, navigator, test_name):
 
     # send last apdu and yield the response
     with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
-        if firmware.device.startswith("nano"):
-                navigator.navigate_until_text_and_compare(
-                    NavIns(NavInsID.WAIT, (0,)),
-                    [
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.BOTH_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.BOTH_CLICK
-                    ],
-                    "Blind",
-                    path=ROOT_SCREENSHOT_PATH,
-                    test_case_name=test_name
-                )
-        else:
-            navigator.navigate_until_text_and_compare(
-                NavIns(NavInsID.WAIT, (0,)),
-                [
-                    NavInsID.CENTERED_FOOTER_TAP,
-                    NavInsID.SWIPE_CENTER_TO_LEFT,
-                    NavInsID.SWIPE_CENTER_TO_LEFT,
-                    NavInsID.USE_CASE_REVIEW_CONFIRM,
-                    NavInsID.USE_CASE_STATUS_DISMISS
-                ],
-                "Blind signing ahead",
-                path=ROOT_SCREENSHOT_PATH,
-                test_case_name=test_name
-            )
-    
+        navigate_blind_review(firmware, navigator, test_name)
+
     response = backend.last_async_response.data
     
     hash, r, s, _ = unpack_sign_tx_response(response)
@@ -340,36 +262,8 @@ def test_tx_v1_l2(firmware, backend, navigator, test_name):
 
     # send last apdu and yield the response
     with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
-        if firmware.device.startswith("nano"):
-                navigator.navigate_until_text_and_compare(
-                    NavIns(NavInsID.WAIT, (0,)),
-                    [
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.BOTH_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.BOTH_CLICK
-                    ],
-                    "Blind",
-                    path=ROOT_SCREENSHOT_PATH,
-                    test_case_name=test_name
-                )
-        else:
-            navigator.navigate_until_text_and_compare(
-                NavIns(NavInsID.WAIT, (0,)),
-                [
-                    NavInsID.CENTERED_FOOTER_TAP,
-                    NavInsID.SWIPE_CENTER_TO_LEFT,
-                    NavInsID.SWIPE_CENTER_TO_LEFT,
-                    NavInsID.USE_CASE_REVIEW_CONFIRM,
-                    NavInsID.USE_CASE_STATUS_DISMISS
-                ],
-                "Blind signing ahead",
-                path=ROOT_SCREENSHOT_PATH,
-                test_case_name=test_name
-            )
-    
+        navigate_blind_review(firmware, navigator, test_name)
+
     response = backend.last_async_response.data
     
     hash, r, s, _ = unpack_sign_tx_response(response)
@@ -430,36 +324,8 @@ def test_tx_v3_l1_data_gas_swap(firmware, backend, navigator, test_name):
 
     # send last apdu and yield the response
     with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
-        if firmware.device.startswith("nano"):
-                navigator.navigate_until_text_and_compare(
-                    NavIns(NavInsID.WAIT, (0,)),
-                    [
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.BOTH_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.BOTH_CLICK
-                    ],
-                    "Blind",
-                    path=ROOT_SCREENSHOT_PATH,
-                    test_case_name=test_name
-                )
-        else:
-            navigator.navigate_until_text_and_compare(
-                NavIns(NavInsID.WAIT, (0,)),
-                [
-                    NavInsID.CENTERED_FOOTER_TAP,
-                    NavInsID.SWIPE_CENTER_TO_LEFT,
-                    NavInsID.SWIPE_CENTER_TO_LEFT,
-                    NavInsID.USE_CASE_REVIEW_CONFIRM,
-                    NavInsID.USE_CASE_STATUS_DISMISS
-                ],
-                "Blind signing ahead",
-                path=ROOT_SCREENSHOT_PATH,
-                test_case_name=test_name
-            )
-    
+        navigate_blind_review(firmware, navigator, test_name)
+
     response = backend.last_async_response.data
     
     hash, r, s, _ = unpack_sign_tx_response(response)
@@ -520,39 +386,75 @@ def test_tx_v3_mint(firmware, backend, navigator, test_name):
 
     # send last apdu and yield the response
     with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
-        if firmware.device.startswith("nano"):
-                navigator.navigate_until_text_and_compare(
-                    NavIns(NavInsID.WAIT, (0,)),
-                    [
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.BOTH_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.RIGHT_CLICK,
-                        NavInsID.BOTH_CLICK
-                    ],
-                    "Blind",
-                    path=ROOT_SCREENSHOT_PATH,
-                    test_case_name=test_name
-                )
-        else:
-            navigator.navigate_until_text_and_compare(
-                NavIns(NavInsID.WAIT, (0,)),
-                [
-                    NavInsID.CENTERED_FOOTER_TAP,
-                    NavInsID.SWIPE_CENTER_TO_LEFT,
-                    NavInsID.SWIPE_CENTER_TO_LEFT,
-                    NavInsID.USE_CASE_REVIEW_CONFIRM,
-                    NavInsID.USE_CASE_STATUS_DISMISS
-                ],
-                "Blind signing ahead",
-                path=ROOT_SCREENSHOT_PATH,
-                test_case_name=test_name
-            )
+        navigate_blind_review(firmware, navigator, test_name)
+
+    response = backend.last_async_response.data
+    
+    hash, r, s, _ = unpack_sign_tx_response(response)
+
+    # Call the external binary with the signature and the public key
+    binary_path = CHECK_SIGNATURE_BINARY_PATH
+    args = ["-t", hash.hex(),
+            "-p", public_key_x.hex(),
+            "-r", r.hex(),
+            "-s", s.hex()]
+    stdout, stderr = call_external_binary(binary_path, *args)
+
+    if stdout:
+        # Convert the output to a boolean value
+        result = stdout.lower() == "true"
+        print(f"Result as boolean: {result}")
+        assert(result)
+    if stderr:
+        print("Standard Error:")
+        print(stderr)
+        assert(False)
+
+# The swap route brings the calldata over 255 felts
+def test_tx_v1_long_swap(firmware, backend, navigator, test_name):
+
+     # Enable blind siging in settings
+    if firmware.device.startswith("nano"):
+
+        instructions = [
+            NavInsID.RIGHT_CLICK,
+            NavInsID.RIGHT_CLICK,
+            NavInsID.BOTH_CLICK,
+            NavInsID.BOTH_CLICK
+        ]
+        navigator.navigate(instructions, screen_change_before_first_instruction=False)
+    else:
+        settings_per_page = 3 if firmware == Firmware.STAX else 2
+        instructions = [
+            NavInsID.USE_CASE_HOME_SETTINGS,
+            NavIns(NavInsID.TOUCH, get_setting_position(firmware, 0, settings_per_page)),
+        ]
+        navigator.navigate(instructions, screen_change_before_first_instruction=False)
     
+     # First we need to get the public key of the device in order to build the transaction    
+    file_path = 'samples/apdu/dpath_0.dat'
+    apdus = read_lines_from_file(file_path)
+    response = backend.exchange_raw(bytes.fromhex(apdus[0])).data
+    public_key_x, _ = unpack_get_public_key_response(response)
+
+    # Send the sign tx device instruction.
+    # As it requires on-screen validation, the function is asynchronous.
+    # It will yield the result when the navigation is done
+    file_path = 'samples/apdu/tx_v1_long_swap.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    # send last apdu and yield the response
+    with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+        navigate_blind_review(firmware, navigator, test_name)
+
     response = backend.last_async_response.data
     
     hash, r, s, _ = unpack_sign_tx_response(response)
+    assert hash.hex() == "0424879032dd5b916aae5b03d8feaa0a5965125d88cf45dd69c5974df68271d4"
 
     # Call the external binary with the signature and the public key
     binary_path = CHECK_SIGNATURE_BINARY_PATH
@@ -570,4 +472,68 @@ def test_tx_v3_mint(firmware, backend, navigator, test_name):
     if stderr:
         print("Standard Error:")
         print(stderr)
-        assert(False)
\ No newline at end of file
+        assert(False)
+
+# The paymaster of a sponsored transaction is shown before the hash
+def test_tx_v3_sponsored_mint(firmware, backend, navigator, test_name):
+
+     # Enable blind siging in settings
+    if firmware.device.startswith("nano"):
+
+        instructions = [
+            NavInsID.RIGHT_CLICK,
+            NavInsID.RIGHT_CLICK,
+            NavInsID.BOTH_CLICK,
+            NavInsID.BOTH_CLICK
+        ]
+        navigator.navigate(instructions, screen_change_before_first_instruction=False)
+    else:
+        settings_per_page = 3 if firmware == Firmware.STAX else 2
+        instructions = [
+            NavInsID.USE_CASE_HOME_SETTINGS,
+            NavIns(NavInsID.TOUCH, get_setting_position(firmware, 0, settings_per_page)),
+        ]
+        navigator.navigate(instructions, screen_change_before_first_instruction=False)
+    
+     # First we need to get the public key of the device in order to build the transaction    
+    file_path = 'samples/apdu/dpath_0.dat'
+    apdus = read_lines_from_file(file_path)
+    response = backend.exchange_raw(bytes.fromhex(apdus[0])).data
+    public_key_x, _ = unpack_get_public_key_response(response)
+
+    # Send the sign tx device instruction.
+    # As it requires on-screen validation, the function is asynchronous.
+    # It will yield the result when the navigation is done
+    file_path = 'samples/apdu/tx_v3_sponsored_mint.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    # send last apdu and yield the response
+    with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+        navigate_blind_review(firmware, navigator, test_name)
+
+    response = backend.last_async_response.data
+    
+    hash, r, s, _ = unpack_sign_tx_response(response)
+    assert hash.hex() == "0426d7712876a868c0c4da8c6c32f2d2cfee3cc80e22a4bf32d59a8a6d33b8a2"
+
+    # Call the external binary with the signature and the public key
+    binary_path = CHECK_SIGNATURE_BINARY_PATH
+    args = ["-t", hash.hex(),
+            "-p", public_key_x.hex(),
+            "-r", r.hex(),
+            "-s", s.hex()]
+    stdout, stderr = call_external_binary(binary_path, *args)
+
+    if stdout:
+        # Convert the output to a boolean value
+        result = stdout.lower() == "true"
+        print(f"Result as boolean: {result}")
+        assert(result)
+    if stderr:
+        print("Standard Error:")
+        print(stderr)
+        assert(False)
@@ -0,0 +1,67 @@
+from application_client.response_unpacker import unpack_get_public_key_response, unpack_sign_tx_response
+from ragger.navigator import NavInsID
+from utils import ROOT_SCREENSHOT_PATH, read_lines_from_file, call_external_binary
+
+CHECK_SIGNATURE_BINARY_PATH = "tools/check-signature/target/debug/check-signature"
+
+# In those tests we check the behavior of the device when asked to sign a DECLARE Tx (clear signing)
+
+# In this test we send to the device a tx to sign and validate it on screen
+# We will ensure that the displayed information is correct by using screenshots comparison
+def test_tx_v3_declare(firmware, backend, navigator, test_name):
+
+    # We need to get the public key of the device to check the signature
+    file_path = 'samples/apdu/dpath_0.dat'
+    apdus = read_lines_from_file(file_path)
+    response = backend.exchange_raw(bytes.fromhex(apdus[0])).data
+    public_key_x, _ = unpack_get_public_key_response(response)
+
+    # Send the sign tx device instruction.
+    # As it requires on-screen validation, the function is asynchronous.
+    # It will yield the result when the navigation is done
+    file_path = 'samples/apdu/tx_v3_declare.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    # send last apdu and yield the response
+    with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+        if firmware.device.startswith("nano"):
+            navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                      [NavInsID.BOTH_CLICK],
+                                                      "Approve",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+        else:
+            navigator.navigate_until_text_and_compare(NavInsID.SWIPE_CENTER_TO_LEFT,
+                                                      [
+                                                          NavInsID.USE_CASE_REVIEW_CONFIRM,
+                                                          NavInsID.USE_CASE_STATUS_DISMISS
+                                                      ],
+                                                      "Hold to sign",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+
+    response = backend.last_async_response.data
+
+    hash, r, s, _ = unpack_sign_tx_response(response)
+
+    # Call the external binary with the signature and the public key
+    binary_path = CHECK_SIGNATURE_BINARY_PATH
+    args = ["-t", hash.hex(),
+            "-p", public_key_x.hex(),
+            "-r", r.hex(),
+            "-s", s.hex()]
+    stdout, stderr = call_external_binary(binary_path, *args)
+
+    if stdout:
+        # Convert the output to a boolean value
+        result = stdout.lower() == "true"
+        print(f"Result as boolean: {result}")
+        assert(result)
+    if stderr:
+        print("Standard Error:")
+        print(stderr)
+        assert(False)
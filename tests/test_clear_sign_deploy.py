@@ -196,4 +196,134 @@ def test_tx_v3_l1_data_gas_deploy_account(firmware, backend, navigator, test_nam
     if stderr:
         print("Standard Error:")
         print(stderr)
-        assert(False)
\ No newline at end of file
+        assert(False)
+
+# Expected hashes of the DEPLOY_ACCOUNT v1 samples whose constructor calldata is 0x1..0xN
+DEPLOY_ACCOUNT_V1_HASHES = {
+    1: "05469607fbe06dccf1f0b1b9166445c781c76f691369244b85c976acb3013ccd",
+    7: "00409a90e37580d8b1e8d0444599c445e44f730220da70eb7aebdfc6b6f3a86e",
+    8: "0617faef1f87a0e184e3387f92d4d302b54f8c3aaa2fd6f8d47c25ebe637e818",
+    50: "07a4580058bb525eebab62fc464e128769c7e1ec6c0e440e96e8b23b8a1739d9",
+}
+
+# In this test the constructor calldata is sent in one or several APDUs (7 felts per APDU)
+# We ensure that the tx hash is the expected one whatever the number of APDUs
+@pytest.mark.parametrize("nb_felts", DEPLOY_ACCOUNT_V1_HASHES.keys())
+def test_tx_v1_deploy_account_calldata(firmware, backend, navigator, test_name, nb_felts):
+
+    file_path = 'samples/apdu/dpath_0.dat'
+    apdus = read_lines_from_file(file_path)
+    response = backend.exchange_raw(bytes.fromhex(apdus[0])).data
+    public_key_x, _ = unpack_get_public_key_response(response)
+
+    file_path = f'samples/apdu/tx_v1_deploy_account_calldata_{nb_felts}.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    # send last apdu and yield the response
+    with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+        if firmware.device.startswith("nano"):
+            navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                      [NavInsID.BOTH_CLICK],
+                                                      "Approve",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+        else:
+            navigator.navigate_until_text_and_compare(NavInsID.SWIPE_CENTER_TO_LEFT,
+                                                      [
+                                                          NavInsID.USE_CASE_REVIEW_CONFIRM,
+                                                          NavInsID.USE_CASE_STATUS_DISMISS
+                                                      ],
+                                                      "Hold to sign",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+
+    response = backend.last_async_response.data
+
+    hash, r, s, _ = unpack_sign_tx_response(response)
+    assert hash.hex() == DEPLOY_ACCOUNT_V1_HASHES[nb_felts]
+
+    # Call the external binary with the signature and the public key
+    binary_path = CHECK_SIGNATURE_BINARY_PATH
+    args = ["-t", hash.hex(),
+            "-p", public_key_x.hex(),
+            "-r", r.hex(),
+            "-s", s.hex()]
+    stdout, stderr = call_external_binary(binary_path, *args)
+
+    if stdout:
+        # Convert the output to a boolean value
+        result = stdout.lower() == "true"
+        print(f"Result as boolean: {result}")
+        assert(result)
+    if stderr:
+        print("Standard Error:")
+        print(stderr)
+        assert(False)
+
+
+# In this test the device checks the deployed address and its owner key, shown on the review:
+# verified address owned by the device key (Argent v3, OpenZeppelin v1) or by another key.
+# The constructor calldata of those known account classes is decoded on the review.
+@pytest.mark.parametrize("sample", [
+    "tx_v3_deploy_account_argent",
+    "tx_v1_deploy_account_oz",
+    "tx_v3_deploy_account_other_owner",
+    "tx_v1_deploy_account_argent_guardian",
+])
+def test_tx_deploy_account_checks(firmware, backend, navigator, test_name, sample):
+
+    file_path = 'samples/apdu/dpath_0.dat'
+    apdus = read_lines_from_file(file_path)
+    response = backend.exchange_raw(bytes.fromhex(apdus[0])).data
+    public_key_x, _ = unpack_get_public_key_response(response)
+
+    file_path = f'samples/apdu/{sample}.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    # send last apdu and yield the response
+    with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+        if firmware.device.startswith("nano"):
+            navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                      [NavInsID.BOTH_CLICK],
+                                                      "Approve",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+        else:
+            navigator.navigate_until_text_and_compare(NavInsID.SWIPE_CENTER_TO_LEFT,
+                                                      [
+                                                          NavInsID.USE_CASE_REVIEW_CONFIRM,
+                                                          NavInsID.USE_CASE_STATUS_DISMISS
+                                                      ],
+                                                      "Hold to sign",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+
+    response = backend.last_async_response.data
+
+    hash, r, s, _ = unpack_sign_tx_response(response)
+
+    # Call the external binary with the signature and the public key
+    binary_path = CHECK_SIGNATURE_BINARY_PATH
+    args = ["-t", hash.hex(),
+            "-p", public_key_x.hex(),
+            "-r", r.hex(),
+            "-s", s.hex()]
+    stdout, stderr = call_external_binary(binary_path, *args)
+
+    if stdout:
+        # Convert the output to a boolean value
+        result = stdout.lower() == "true"
+        print(f"Result as boolean: {result}")
+        assert(result)
+    if stderr:
+        print("Standard Error:")
+        print(stderr)
+        assert(False)
@@ -2,7 +2,11 @@ import pytest
 
 from application_client.response_unpacker import unpack_get_public_key_response, unpack_sign_tx_response, Errors
 from ragger.navigator import NavInsID, NavIns
-from utils import ROOT_SCREENSHOT_PATH, read_lines_from_file, call_external_binary
+from ragger.error import ExceptionRAPDU
+from ragger.firmware import Firmware
+from application_client.token_info import token_info_payload
+from application_client.call_descriptor import call_descriptor_payloads, CONTRACT_ADDRESS, TOKEN_AMOUNT
+from utils import ROOT_SCREENSHOT_PATH, read_lines_from_file, call_external_binary, get_setting_position
 
 CHECK_SIGNATURE_BINARY_PATH = "tools/check-signature/target/debug/check-signature"
 
@@ -364,4 +368,646 @@ def test_tx_v3_l1_data_gas_transfer(firmware, backend, navigator, test_name):
     if stderr:
         print("Standard Error:")
         print(stderr)
-        assert(False)
\ No newline at end of file
+        assert(False)
+
+# In this test we send to the device a multicall tx (3 transfers) to sign and validate it on screen
+# Each call is summarized on its own page
+def test_tx_v3_multi_transfer(firmware, backend, navigator, test_name):
+
+    # First we need to get the public key of the device in order to build the transaction
+    file_path = 'samples/apdu/dpath_0.dat'
+    apdus = read_lines_from_file(file_path)
+    response = backend.exchange_raw(bytes.fromhex(apdus[0])).data
+    public_key_x, _ = unpack_get_public_key_response(response)
+
+    # Send the sign tx device instruction.
+    # As it requires on-screen validation, the function is asynchronous.
+    # It will yield the result when the navigation is done
+    file_path = 'samples/apdu/tx_v3_multi_transfer.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    # send last apdu and yield the response
+    with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+        if firmware.device.startswith("nano"):
+            navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                      [NavInsID.BOTH_CLICK],
+                                                      "Approve",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+        else:
+            navigator.navigate_until_text_and_compare(NavInsID.SWIPE_CENTER_TO_LEFT,
+                                                      [
+                                                          NavInsID.USE_CASE_REVIEW_CONFIRM,
+                                                          NavInsID.USE_CASE_STATUS_DISMISS
+                                                      ],
+                                                      "Hold to sign",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+
+    response = backend.last_async_response.data
+
+    hash, r, s, _ = unpack_sign_tx_response(response)
+
+    # Call the external binary with the signature and the public key
+    binary_path = CHECK_SIGNATURE_BINARY_PATH
+    args = ["-t", hash.hex(),
+            "-p", public_key_x.hex(),
+            "-r", r.hex(),
+            "-s", s.hex()]
+    stdout, stderr = call_external_binary(binary_path, *args)
+
+    if stdout:
+        # Convert the output to a boolean value
+        result = stdout.lower() == "true"
+        print(f"Result as boolean: {result}")
+        assert(result)
+    if stderr:
+        print("Standard Error:")
+        print(stderr)
+        assert(False)
+
+def test_tx_v3_approve_and_transfer_from(firmware, backend, navigator, test_name):
+
+    # First we need to get the public key of the device in order to build the transaction
+    file_path = 'samples/apdu/dpath_0.dat'
+    apdus = read_lines_from_file(file_path)
+    response = backend.exchange_raw(bytes.fromhex(apdus[0])).data
+    public_key_x, _ = unpack_get_public_key_response(response)
+
+    # Send the sign tx device instruction.
+    # As it requires on-screen validation, the function is asynchronous.
+    # It will yield the result when the navigation is done
+    file_path = 'samples/apdu/tx_v3_approve_and_transfer_from.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    # send last apdu and yield the response
+    with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+        if firmware.device.startswith("nano"):
+            navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                      [NavInsID.BOTH_CLICK],
+                                                      "Approve",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+        else:
+            navigator.navigate_until_text_and_compare(NavInsID.SWIPE_CENTER_TO_LEFT,
+                                                      [
+                                                          NavInsID.USE_CASE_REVIEW_CONFIRM,
+                                                          NavInsID.USE_CASE_STATUS_DISMISS
+                                                      ],
+                                                      "Hold to sign",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+
+    response = backend.last_async_response.data
+
+    hash, r, s, _ = unpack_sign_tx_response(response)
+
+    # Call the external binary with the signature and the public key
+    binary_path = CHECK_SIGNATURE_BINARY_PATH
+    args = ["-t", hash.hex(),
+            "-p", public_key_x.hex(),
+            "-r", r.hex(),
+            "-s", s.hex()]
+    stdout, stderr = call_external_binary(binary_path, *args)
+
+    if stdout:
+        # Convert the output to a boolean value
+        result = stdout.lower() == "true"
+        print(f"Result as boolean: {result}")
+        assert(result)
+    if stderr:
+        print("Standard Error:")
+        print(stderr)
+        assert(False)
+
+def test_tx_v3_transfer_u256_high(firmware, backend, navigator, test_name):
+
+    # First we need to get the public key of the device in order to build the transaction
+    file_path = 'samples/apdu/dpath_0.dat'
+    apdus = read_lines_from_file(file_path)
+    response = backend.exchange_raw(bytes.fromhex(apdus[0])).data
+    public_key_x, _ = unpack_get_public_key_response(response)
+
+    # Send the sign tx device instruction.
+    # As it requires on-screen validation, the function is asynchronous.
+    # It will yield the result when the navigation is done
+    file_path = 'samples/apdu/tx_v3_transfer_u256_high.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    # send last apdu and yield the response
+    with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+        if firmware.device.startswith("nano"):
+            navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                      [NavInsID.BOTH_CLICK],
+                                                      "Approve",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+        else:
+            navigator.navigate_until_text_and_compare(NavInsID.SWIPE_CENTER_TO_LEFT,
+                                                      [
+                                                          NavInsID.USE_CASE_REVIEW_CONFIRM,
+                                                          NavInsID.USE_CASE_STATUS_DISMISS
+                                                      ],
+                                                      "Hold to sign",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+
+    response = backend.last_async_response.data
+
+    hash, r, s, _ = unpack_sign_tx_response(response)
+
+    # Call the external binary with the signature and the public key
+    binary_path = CHECK_SIGNATURE_BINARY_PATH
+    args = ["-t", hash.hex(),
+            "-p", public_key_x.hex(),
+            "-r", r.hex(),
+            "-s", s.hex()]
+    stdout, stderr = call_external_binary(binary_path, *args)
+
+    if stdout:
+        # Convert the output to a boolean value
+        result = stdout.lower() == "true"
+        print(f"Result as boolean: {result}")
+        assert(result)
+    if stderr:
+        print("Standard Error:")
+        print(stderr)
+        assert(False)
+
+def test_tx_v3_transfer_provided_token(firmware, backend, navigator, test_name):
+
+    # First we need to get the public key of the device in order to build the transaction
+    file_path = 'samples/apdu/dpath_0.dat'
+    apdus = read_lines_from_file(file_path)
+    response = backend.exchange_raw(bytes.fromhex(apdus[0])).data
+    public_key_x, _ = unpack_get_public_key_response(response)
+
+    # USDC is not in the app token list: provide its information signed with the test key
+    backend.exchange(cla=0x5A,
+                     ins=0x09,
+                     p1=0x00,
+                     p2=0x00,
+                     data=token_info_payload("0x053c91253bc9682c04929ca02ed00b3e423f6710d2ee7e0d5ebb06f3ecf368a8", "USDC", 6))
+
+    # Send the sign tx device instruction.
+    # As it requires on-screen validation, the function is asynchronous.
+    # It will yield the result when the navigation is done
+    file_path = 'samples/apdu/tx_v3_transfer_USDC.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    # send last apdu and yield the response
+    with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+        if firmware.device.startswith("nano"):
+            navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                      [NavInsID.BOTH_CLICK],
+                                                      "Approve",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+        else:
+            navigator.navigate_until_text_and_compare(NavInsID.SWIPE_CENTER_TO_LEFT,
+                                                      [
+                                                          NavInsID.USE_CASE_REVIEW_CONFIRM,
+                                                          NavInsID.USE_CASE_STATUS_DISMISS
+                                                      ],
+                                                      "Hold to sign",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+
+    response = backend.last_async_response.data
+
+    hash, r, s, _ = unpack_sign_tx_response(response)
+
+    # Call the external binary with the signature and the public key
+    binary_path = CHECK_SIGNATURE_BINARY_PATH
+    args = ["-t", hash.hex(),
+            "-p", public_key_x.hex(),
+            "-r", r.hex(),
+            "-s", s.hex()]
+    stdout, stderr = call_external_binary(binary_path, *args)
+
+    if stdout:
+        # Convert the output to a boolean value
+        result = stdout.lower() == "true"
+        print(f"Result as boolean: {result}")
+        assert(result)
+    if stderr:
+        print("Standard Error:")
+        print(stderr)
+        assert(False)
+
+def test_tx_v3_described_call(firmware, backend, navigator, test_name):
+
+    # First we need to get the public key of the device in order to build the transaction
+    file_path = 'samples/apdu/dpath_0.dat'
+    apdus = read_lines_from_file(file_path)
+    response = backend.exchange_raw(bytes.fromhex(apdus[0])).data
+    public_key_x, _ = unpack_get_public_key_response(response)
+
+    # Describe the deposit entrypoint of the lending contract, signed with the test key
+    payloads = call_descriptor_payloads("0x04c0a5193d58f74fbace4b74dcf65481e734ed1714121bdc571da345540efa05",
+                                        "0x00c73f681176fc7b3f9693986fd7b14581e8d540519e27400e88b8713932be01",
+                                        "Deposit",
+                                        [("Token", CONTRACT_ADDRESS, 0), ("Amount", TOKEN_AMOUNT, 0)])
+    for p1, payload in payloads:
+        backend.exchange(cla=0x5A, ins=0x0A, p1=p1, p2=0x00, data=payload)
+
+    # Send the sign tx device instruction.
+    # As it requires on-screen validation, the function is asynchronous.
+    # It will yield the result when the navigation is done
+    file_path = 'samples/apdu/tx_v3_described_call.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    # send last apdu and yield the response
+    with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+        if firmware.device.startswith("nano"):
+            navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                      [NavInsID.BOTH_CLICK],
+                                                      "Approve",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+        else:
+            navigator.navigate_until_text_and_compare(NavInsID.SWIPE_CENTER_TO_LEFT,
+                                                      [
+                                                          NavInsID.USE_CASE_REVIEW_CONFIRM,
+                                                          NavInsID.USE_CASE_STATUS_DISMISS
+                                                      ],
+                                                      "Hold to sign",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+
+    response = backend.last_async_response.data
+
+    hash, r, s, _ = unpack_sign_tx_response(response)
+
+    # Call the external binary with the signature and the public key
+    binary_path = CHECK_SIGNATURE_BINARY_PATH
+    args = ["-t", hash.hex(),
+            "-p", public_key_x.hex(),
+            "-r", r.hex(),
+            "-s", s.hex()]
+    stdout, stderr = call_external_binary(binary_path, *args)
+
+    if stdout:
+        # Convert the output to a boolean value
+        result = stdout.lower() == "true"
+        print(f"Result as boolean: {result}")
+        assert(result)
+    if stderr:
+        print("Standard Error:")
+        print(stderr)
+        assert(False)
+
+# In this test we send to the device a transfer sponsored by a paymaster to sign and validate it on screen
+# The paymaster_data spans two APDUs and the paymaster address is shown
+def test_tx_v3_sponsored_transfer(firmware, backend, navigator, test_name):
+
+    # First we need to get the public key of the device in order to build the transaction
+    file_path = 'samples/apdu/dpath_0.dat'
+    apdus = read_lines_from_file(file_path)
+    response = backend.exchange_raw(bytes.fromhex(apdus[0])).data
+    public_key_x, _ = unpack_get_public_key_response(response)
+
+    # Send the sign tx device instruction.
+    # As it requires on-screen validation, the function is asynchronous.
+    # It will yield the result when the navigation is done
+    file_path = 'samples/apdu/tx_v3_sponsored_transfer.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    # send last apdu and yield the response
+    with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+        if firmware.device.startswith("nano"):
+            navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                      [NavInsID.BOTH_CLICK],
+                                                      "Approve",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+        else:
+            navigator.navigate_until_text_and_compare(NavInsID.SWIPE_CENTER_TO_LEFT,
+                                                      [
+                                                          NavInsID.USE_CASE_REVIEW_CONFIRM,
+                                                          NavInsID.USE_CASE_STATUS_DISMISS
+                                                      ],
+                                                      "Hold to sign",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+
+    response = backend.last_async_response.data
+
+    hash, r, s, _ = unpack_sign_tx_response(response)
+
+    # Call the external binary with the signature and the public key
+    binary_path = CHECK_SIGNATURE_BINARY_PATH
+    args = ["-t", hash.hex(),
+            "-p", public_key_x.hex(),
+            "-r", r.hex(),
+            "-s", s.hex()]
+    stdout, stderr = call_external_binary(binary_path, *args)
+
+    if stdout:
+        # Convert the output to a boolean value
+        result = stdout.lower() == "true"
+        print(f"Result as boolean: {result}")
+        assert(result)
+    if stderr:
+        print("Standard Error:")
+        print(stderr)
+        assert(False)
+
+# In this test we send to the device the first transaction of a counterfactual account to sign and validate it on screen
+# The account deployed along with the transfer is decoded from account_deployment_data
+def test_tx_v3_deploy_and_transfer(firmware, backend, navigator, test_name):
+
+    # First we need to get the public key of the device in order to build the transaction
+    file_path = 'samples/apdu/dpath_0.dat'
+    apdus = read_lines_from_file(file_path)
+    response = backend.exchange_raw(bytes.fromhex(apdus[0])).data
+    public_key_x, _ = unpack_get_public_key_response(response)
+
+    # Send the sign tx device instruction.
+    # As it requires on-screen validation, the function is asynchronous.
+    # It will yield the result when the navigation is done
+    file_path = 'samples/apdu/tx_v3_deploy_and_transfer.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    # send last apdu and yield the response
+    with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+        if firmware.device.startswith("nano"):
+            navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                      [NavInsID.BOTH_CLICK],
+                                                      "Approve",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+        else:
+            navigator.navigate_until_text_and_compare(NavInsID.SWIPE_CENTER_TO_LEFT,
+                                                      [
+                                                          NavInsID.USE_CASE_REVIEW_CONFIRM,
+                                                          NavInsID.USE_CASE_STATUS_DISMISS
+                                                      ],
+                                                      "Hold to sign",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+
+    response = backend.last_async_response.data
+
+    hash, r, s, _ = unpack_sign_tx_response(response)
+
+    # Call the external binary with the signature and the public key
+    binary_path = CHECK_SIGNATURE_BINARY_PATH
+    args = ["-t", hash.hex(),
+            "-p", public_key_x.hex(),
+            "-r", r.hex(),
+            "-s", s.hex()]
+    stdout, stderr = call_external_binary(binary_path, *args)
+
+    if stdout:
+        # Convert the output to a boolean value
+        result = stdout.lower() == "true"
+        print(f"Result as boolean: {result}")
+        assert(result)
+    if stderr:
+        print("Standard Error:")
+        print(stderr)
+        assert(False)
+
+# In this test we send to the device a transfer whose nonce uses L2 data availability to sign and validate it on screen
+# A warning about the data availability modes is shown
+def test_tx_v3_l2_da_transfer(firmware, backend, navigator, test_name):
+
+    # First we need to get the public key of the device in order to build the transaction
+    file_path = 'samples/apdu/dpath_0.dat'
+    apdus = read_lines_from_file(file_path)
+    response = backend.exchange_raw(bytes.fromhex(apdus[0])).data
+    public_key_x, _ = unpack_get_public_key_response(response)
+
+    # Send the sign tx device instruction.
+    # As it requires on-screen validation, the function is asynchronous.
+    # It will yield the result when the navigation is done
+    file_path = 'samples/apdu/tx_v3_l2_da_transfer.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    # send last apdu and yield the response
+    with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+        if firmware.device.startswith("nano"):
+            navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                      [NavInsID.BOTH_CLICK],
+                                                      "Approve",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+        else:
+            navigator.navigate_until_text_and_compare(NavInsID.SWIPE_CENTER_TO_LEFT,
+                                                      [
+                                                          NavInsID.USE_CASE_REVIEW_CONFIRM,
+                                                          NavInsID.USE_CASE_STATUS_DISMISS
+                                                      ],
+                                                      "Hold to sign",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+
+    response = backend.last_async_response.data
+
+    hash, r, s, _ = unpack_sign_tx_response(response)
+
+    # Call the external binary with the signature and the public key
+    binary_path = CHECK_SIGNATURE_BINARY_PATH
+    args = ["-t", hash.hex(),
+            "-p", public_key_x.hex(),
+            "-r", r.hex(),
+            "-s", s.hex()]
+    stdout, stderr = call_external_binary(binary_path, *args)
+
+    if stdout:
+        # Convert the output to a boolean value
+        result = stdout.lower() == "true"
+        print(f"Result as boolean: {result}")
+        assert(result)
+    if stderr:
+        print("Standard Error:")
+        print(stderr)
+        assert(False)
+
+# In this test we send to the device a transaction for an app-chain, unknown to the device
+# We check that the user is warned about the network before the review and can reject the transaction
+def test_tx_v3_unknown_network_refused(firmware, backend, navigator, test_name):
+
+    file_path = 'samples/apdu/tx_v3_unknown_network.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    with pytest.raises(ExceptionRAPDU) as e:
+        with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+            if firmware.device.startswith("nano"):
+                navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                          [NavInsID.BOTH_CLICK],
+                                                          "Reject",
+                                                          ROOT_SCREENSHOT_PATH,
+                                                          test_name)
+            else:
+                instructions = [
+                    NavInsID.USE_CASE_CHOICE_REJECT,
+                    NavInsID.USE_CASE_STATUS_DISMISS
+                ]
+                navigator.navigate_and_compare(ROOT_SCREENSHOT_PATH,
+                                               test_name,
+                                               instructions)
+    assert e.value.status == Errors.SW_UNKNOWN_CHAIN
+
+# In this test we cap STRK fees in the settings and refuse the warning shown
+# for a tx whose max fee (20 STRK) is over the cap (10 STRK)
+def test_tx_v3_over_fee_cap_refused(firmware, backend, navigator, test_name):
+
+    # Enable the STRK fee cap in settings
+    if firmware.device.startswith("nano"):
+        instructions = [
+            NavInsID.RIGHT_CLICK,
+            NavInsID.RIGHT_CLICK,
+            NavInsID.BOTH_CLICK,
+            NavInsID.RIGHT_CLICK,
+            NavInsID.BOTH_CLICK
+        ]
+        navigator.navigate(instructions, screen_change_before_first_instruction=False)
+    else:
+        settings_per_page = 3 if firmware == Firmware.STAX else 2
+        instructions = [
+            NavInsID.USE_CASE_HOME_SETTINGS,
+            NavIns(NavInsID.TOUCH, get_setting_position(firmware, 1, settings_per_page)),
+        ]
+        navigator.navigate(instructions, screen_change_before_first_instruction=False)
+
+    file_path = 'samples/apdu/tx_v3_high_fee_transfer.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    with pytest.raises(ExceptionRAPDU) as e:
+        with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+            if firmware.device.startswith("nano"):
+                navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                          [NavInsID.BOTH_CLICK],
+                                                          "Reject",
+                                                          ROOT_SCREENSHOT_PATH,
+                                                          test_name)
+            else:
+                instructions = [
+                    NavInsID.USE_CASE_CHOICE_REJECT,
+                    NavInsID.USE_CASE_STATUS_DISMISS
+                ]
+                navigator.navigate_and_compare(ROOT_SCREENSHOT_PATH,
+                                               test_name,
+                                               instructions)
+    assert e.value.status == Errors.SW_FEE_CAP_EXCEEDED
+
+# In this test we show addresses in blocks of 4 characters and validate a tx on screen
+def test_tx_v3_transfer_grouped_addresses(firmware, backend, navigator, test_name):
+
+    # Enable grouped addresses in settings
+    if firmware.device.startswith("nano"):
+        instructions = [
+            NavInsID.RIGHT_CLICK,
+            NavInsID.RIGHT_CLICK,
+            NavInsID.BOTH_CLICK,
+            NavInsID.RIGHT_CLICK,
+            NavInsID.RIGHT_CLICK,
+            NavInsID.RIGHT_CLICK,
+            NavInsID.RIGHT_CLICK,
+            NavInsID.BOTH_CLICK
+        ]
+        navigator.navigate(instructions, screen_change_before_first_instruction=False)
+    else:
+        settings_per_page = 3 if firmware == Firmware.STAX else 2
+        instructions = [NavInsID.USE_CASE_HOME_SETTINGS]
+        instructions += [NavInsID.USE_CASE_SETTINGS_NEXT] * (4 // settings_per_page)
+        instructions += [NavIns(NavInsID.TOUCH, get_setting_position(firmware, 4, settings_per_page))]
+        navigator.navigate(instructions, screen_change_before_first_instruction=False)
+
+    # First we need to get the public key of the device in order to build the transaction
+    file_path = 'samples/apdu/dpath_0.dat'
+    apdus = read_lines_from_file(file_path)
+    response = backend.exchange_raw(bytes.fromhex(apdus[0])).data
+    public_key_x, _ = unpack_get_public_key_response(response)
+
+    # Send the sign tx device instruction.
+    # As it requires on-screen validation, the function is asynchronous.
+    # It will yield the result when the navigation is done
+    file_path = 'samples/apdu/tx_v3_transfer_ETH.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    # send last apdu and yield the response
+    with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+        if firmware.device.startswith("nano"):
+            navigator.navigate_until_text_and_compare(NavInsID.RIGHT_CLICK,
+                                                      [NavInsID.BOTH_CLICK],
+                                                      "Approve",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+        else:
+            navigator.navigate_until_text_and_compare(NavInsID.SWIPE_CENTER_TO_LEFT,
+                                                      [
+                                                          NavInsID.USE_CASE_REVIEW_CONFIRM,
+                                                          NavInsID.USE_CASE_STATUS_DISMISS
+                                                      ],
+                                                      "Hold to sign",
+                                                      ROOT_SCREENSHOT_PATH,
+                                                      test_name)
+
+    response = backend.last_async_response.data
+
+    hash, r, s, _ = unpack_sign_tx_response(response)
+
+    # Call the external binary with the signature and the public key
+    binary_path = CHECK_SIGNATURE_BINARY_PATH
+    args = ["-t", hash.hex(),
+            "-p", public_key_x.hex(),
+            "-r", r.hex(),
+            "-s", s.hex()]
+    stdout, stderr = call_external_binary(binary_path, *args)
+
+    if stdout:
+        # Convert the output to a boolean value
+        result = stdout.lower() == "true"
+        print(f"Result as boolean: {result}")
+        assert(result)
+    if stderr:
+        print("Standard Error:")
+        print(stderr)
+        assert(False)
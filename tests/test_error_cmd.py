@@ -2,6 +2,9 @@ import pytest
 
 from ragger.error import ExceptionRAPDU
 from application_client.response_unpacker import Errors
+from utils import read_lines_from_file
+from application_client.token_info import token_info_payload
+from application_client.call_descriptor import call_descriptor_payloads, FELT
 from enum import IntEnum
 
 CLA: int = 0x5A
@@ -12,6 +15,9 @@ class InsType(IntEnum):
     SIGN_HASH      = 0x02
     SIGN_TX        = 0x03
     SIGN_TXv1      = 0x04
+    SIGN_TYPED_DATA = 0x08
+    PROVIDE_TOKEN_INFO = 0x09
+    PROVIDE_CALL_DESCRIPTOR = 0x0A
 
 # Ensure the app returns an error when a bad CLA is used
 def test_bad_cla(backend):
@@ -63,3 +69,263 @@ def test_wrong_data_length(backend):
 #                          p2=P2.P2_MORE,
 #                          data=b"abcde")  # data is not parsed in this case
 #     assert e.value.status == Errors.SW_BAD_STATE
+
+# Ensure the app rejects a typed data message whose types have not been defined
+def test_typed_data_unknown_type(backend):
+    backend.exchange(cla=CLA,
+                     ins=InsType.SIGN_TYPED_DATA,
+                     p1=0x00,
+                     p2=0x00,
+                     data=bytes.fromhex("80000a55c741e9c9c47a6028800000008000000000000000"))
+    backend.exchange(cla=CLA,
+                     ins=InsType.SIGN_TYPED_DATA,
+                     p1=0x01,
+                     p2=0x00,
+                     data=bytes(32))
+    # Definition of an empty "Mail" struct, the domain separator type being left undefined
+    backend.exchange(cla=CLA,
+                     ins=InsType.SIGN_TYPED_DATA,
+                     p1=0x02,
+                     p2=0x00,
+                     data=bytes.fromhex("000700044d61696c00"))
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA,
+                         ins=InsType.SIGN_TYPED_DATA,
+                         p1=0x03,
+                         p2=0x00,
+                         data=b"StarknetDomain")
+    assert e.value.status == Errors.SW_TYPED_DATA_UNKNOWN_TYPE
+
+# Ensure the app rejects a typed data account address which is not 32 bytes long
+def test_typed_data_invalid_account_address(backend):
+    backend.exchange(cla=CLA,
+                     ins=InsType.SIGN_TYPED_DATA,
+                     p1=0x00,
+                     p2=0x00,
+                     data=bytes.fromhex("80000a55c741e9c9c47a6028800000008000000000000000"))
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA,
+                         ins=InsType.SIGN_TYPED_DATA,
+                         p1=0x01,
+                         p2=0x00,
+                         data=bytes.fromhex("07e00d496e324876bbc8531f2d9a82bf154d1a04a50218ee74cdd372f75a55"))
+    assert e.value.status == Errors.SW_TYPED_DATA_INVALID_VALUE
+
+# Ensure the app rejects typed data values sent before the type definitions
+def test_typed_data_step_skipped(backend):
+    backend.exchange(cla=CLA,
+                     ins=InsType.SIGN_TYPED_DATA,
+                     p1=0x00,
+                     p2=0x00,
+                     data=bytes.fromhex("80000a55c741e9c9c47a6028800000008000000000000000"))
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA,
+                         ins=InsType.SIGN_TYPED_DATA,
+                         p1=0x04,
+                         p2=0x00,
+                         data=bytes(32))
+    assert e.value.status == Errors.SW_WRONG_TX_STATE
+
+# Ensure the app rejects token information not signed by the trusted key
+def test_provide_token_info_bad_signature(backend):
+    data = token_info_payload("0x053c91253bc9682c04929ca02ed00b3e423f6710d2ee7e0d5ebb06f3ecf368a8", "USDC", 6, private_key=0x1234)
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA, ins=InsType.PROVIDE_TOKEN_INFO, data=data)
+    assert e.value.status == Errors.SW_TOKEN_INFO_INVALID_SIGNATURE
+
+
+# Ensure the app rejects malformed token information
+def test_provide_token_info_invalid_data(backend):
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA, ins=InsType.PROVIDE_TOKEN_INFO, data=bytes(34))
+    assert e.value.status == Errors.SW_TOKEN_INFO_INVALID_DATA
+
+
+# Ensure the app rejects a call descriptor not signed by the trusted key
+def test_call_descriptor_bad_signature(backend):
+    payloads = call_descriptor_payloads("0x04c0a5193d58f74fbace4b74dcf65481e734ed1714121bdc571da345540efa05",
+                                        "0x00c73f681176fc7b3f9693986fd7b14581e8d540519e27400e88b8713932be01",
+                                        "Deposit",
+                                        [("Amount", FELT, 0)],
+                                        private_key=0x1234)
+    for p1, payload in payloads[:-1]:
+        backend.exchange(cla=CLA, ins=InsType.PROVIDE_CALL_DESCRIPTOR, p1=p1, data=payload)
+    with pytest.raises(ExceptionRAPDU) as e:
+        p1, payload = payloads[-1]
+        backend.exchange(cla=CLA, ins=InsType.PROVIDE_CALL_DESCRIPTOR, p1=p1, data=payload)
+    assert e.value.status == Errors.SW_CALL_DESCRIPTOR_INVALID_SIGNATURE
+
+
+# Ensure the app rejects call descriptor parameters sent before the descriptor start
+def test_call_descriptor_unexpected_param(backend):
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA, ins=InsType.PROVIDE_CALL_DESCRIPTOR, p1=0x01, data=bytes([1]) + b"a" + bytes([FELT]))
+    assert e.value.status == Errors.SW_CALL_DESCRIPTOR_UNEXPECTED_DATA
+
+
+# Ensure the app rejects paymaster data exceeding what it can hold
+def test_paymaster_data_too_long(backend):
+    apdus = read_lines_from_file('samples/apdu/tx_v3_sponsored_transfer.dat')
+    # path, tx fields and fees
+    for apdu in apdus[:3]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+    chunk = bytes(32 * 7)
+    for _ in range(2):
+        backend.exchange(cla=CLA, ins=InsType.SIGN_TX, p1=0x03, p2=0x01, data=chunk)
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA, ins=InsType.SIGN_TX, p1=0x03, p2=0x00, data=chunk)
+    assert e.value.status == Errors.SW_TOO_MUCH_DATA
+
+
+# Ensure the app rejects an unknown paymaster data chunk flag
+def test_paymaster_data_bad_p2(backend):
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA, ins=InsType.SIGN_TX, p1=0x03, p2=0x02)
+    assert e.value.status == Errors.SW_WRONG_P1P2
+
+
+# Ensure the app rejects resource bounds not in the L1_GAS, L2_GAS, L1_DATA order
+def test_fees_invalid_resource_bounds(backend):
+    apdus = read_lines_from_file('samples/apdu/tx_v3_l1_data_gas_transfer.dat')
+    # path and tx fields
+    for apdu in apdus[:2]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+    fees = bytearray(bytes.fromhex(apdus[2])[5:])
+    # swap the L1_GAS and L2_GAS bounds
+    fees[32:64], fees[64:96] = fees[64:96], fees[32:64]
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA, ins=InsType.SIGN_TX, p1=0x02, data=bytes(fees))
+    assert e.value.status == Errors.SW_INVALID_RESOURCE_BOUNDS
+
+
+# Ensure the app rejects an unknown data availability mode
+def test_unknown_da_mode(backend):
+    apdus = read_lines_from_file('samples/apdu/tx_v3_l1_data_gas_transfer.dat')
+    backend.exchange_raw(bytes.fromhex(apdus[0]))
+    fields = bytearray(bytes.fromhex(apdus[1])[5:])
+    # fee DA mode 2
+    fields[127] = 2
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA, ins=InsType.SIGN_TX, p1=0x01, data=bytes(fields))
+    assert e.value.status == Errors.SW_UNKNOWN_DA_MODE
+
+
+# Ensure the app rejects tx fields shorter than expected instead of crashing
+def test_tx_fields_too_short(backend):
+    apdus = read_lines_from_file('samples/apdu/tx_v3_l1_data_gas_transfer.dat')
+    backend.exchange_raw(bytes.fromhex(apdus[0]))
+    fields = bytes.fromhex(apdus[1])[5:]
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA, ins=InsType.SIGN_TX, p1=0x01, data=fields[:-32])
+    assert e.value.status == Errors.SW_INVALID_TX_LENGTH
+
+
+# Ensure the app rejects an unknown call step
+def test_call_bad_step(backend):
+    apdus = read_lines_from_file('samples/apdu/tx_v3_transfer_ETH.dat')
+    # path, tx fields, fees, paymaster data, account deployment data and number of calls
+    for apdu in apdus[:6]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA, ins=InsType.SIGN_TX, p1=0x06, p2=0x02, data=bytes(96))
+    assert e.value.status == Errors.SW_INVALID_TX_STEP
+
+
+# Ensure the app rejects tx steps skipping the fees
+def test_tx_step_skipped(backend):
+    apdus = read_lines_from_file('samples/apdu/tx_v3_transfer_ETH.dat')
+    # path and tx fields
+    for apdu in apdus[:2]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange_raw(bytes.fromhex(apdus[3]))
+    assert e.value.status == Errors.SW_WRONG_TX_STATE
+
+
+# Ensure the app rejects a repeated tx step, and aborts the tx until a new one starts
+def test_tx_step_repeated(backend):
+    apdus = read_lines_from_file('samples/apdu/tx_v3_transfer_ETH.dat')
+    # path, tx fields and fees
+    for apdu in apdus[:3]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange_raw(bytes.fromhex(apdus[2]))
+    assert e.value.status == Errors.SW_WRONG_TX_STATE
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange_raw(bytes.fromhex(apdus[3]))
+    assert e.value.status == Errors.SW_WRONG_TX_STATE
+    # a new tx is accepted
+    for apdu in apdus[:4]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+
+# Ensure the app rejects steps of another instruction than the tx in progress
+def test_tx_step_other_instruction(backend):
+    apdus = read_lines_from_file('samples/apdu/tx_v3_transfer_ETH.dat')
+    backend.exchange_raw(bytes.fromhex(apdus[0]))
+    v1_apdus = read_lines_from_file('samples/apdu/tx_v1_transfer_ETH_0.dat')
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange_raw(bytes.fromhex(v1_apdus[1]))
+    assert e.value.status == Errors.SW_WRONG_TX_STATE
+
+
+# Ensure the app rejects more calldata than announced for a call
+def test_calldata_overflow(backend):
+    apdus = read_lines_from_file('samples/apdu/tx_v3_transfer_ETH.dat')
+    # path, tx fields, fees, paymaster data, account deployment data and number of calls
+    for apdu in apdus[:6]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+    call = bytes.fromhex(apdus[6])[5:]
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA, ins=InsType.SIGN_TX, p1=0x06, p2=0x00, data=call + bytes(32))
+    assert e.value.status == Errors.SW_CALLDATA_OVERFLOW
+
+
+# Ensure the app rejects more calls than announced for a tx
+def test_too_many_calls(backend):
+    apdus = read_lines_from_file('samples/apdu/tx_v3_transfer_ETH.dat')
+    # path, tx fields, fees, paymaster data and account deployment data
+    for apdu in apdus[:5]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+    # announce no call
+    backend.exchange(cla=CLA, ins=InsType.SIGN_TX, p1=0x05, data=bytes(32))
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange_raw(bytes.fromhex(apdus[6]))
+    assert e.value.status == Errors.SW_TOO_MANY_CALLS
+
+
+# Ensure the app rejects a number of calls it cannot count instead of truncating it
+def test_too_large_nb_calls(backend):
+    apdus = read_lines_from_file('samples/apdu/tx_v3_transfer_ETH.dat')
+    # path, tx fields, fees, paymaster data and account deployment data
+    for apdu in apdus[:5]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA, ins=InsType.SIGN_TX, p1=0x05, data=(2**64 + 1).to_bytes(32, "big"))
+    assert e.value.status == Errors.SW_INVALID_DATA
+
+
+# Ensure the app rejects calldata added to a complete call, which would duplicate it
+def test_add_calldata_to_complete_call(backend):
+    apdus = read_lines_from_file('samples/apdu/tx_v3_approve_and_transfer_from.dat')
+    # path, tx fields, fees, paymaster data, account deployment data, number of calls
+    # and the first of the 3 calls
+    for apdu in apdus[:7]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA, ins=InsType.SIGN_TX, p1=0x06, p2=0x01, data=b"")
+    assert e.value.status == Errors.SW_WRONG_TX_STATE
+
+
+# Ensure the app rejects a new call while the previous one still expects calldata
+def test_new_call_before_complete_call(backend):
+    apdus = read_lines_from_file('samples/apdu/tx_v3_approve_and_transfer_from.dat')
+    # path, tx fields, fees, paymaster data, account deployment data and number of calls
+    for apdu in apdus[:6]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+    # to, selector, calldata length of 2 and a single calldata
+    call = bytes(64) + (2).to_bytes(32, "big") + bytes(32)
+    backend.exchange(cla=CLA, ins=InsType.SIGN_TX, p1=0x06, p2=0x00, data=call)
+    with pytest.raises(ExceptionRAPDU) as e:
+        backend.exchange(cla=CLA, ins=InsType.SIGN_TX, p1=0x06, p2=0x00, data=call)
+    assert e.value.status == Errors.SW_WRONG_TX_STATE
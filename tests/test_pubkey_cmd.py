@@ -65,5 +65,5 @@ def test_get_public_key_confirm_refused(firmware, backend, navigator, test_name)
                 navigator.navigate_and_compare(ROOT_SCREENSHOT_PATH,
                                             test_name,
                                             instructions)
-    assert e.value.status == Errors.SW_DENY
+    assert e.value.status == Errors.SW_USER_CANCELLED
     assert len(e.value.data) == 0
\ No newline at end of file
@@ -4,34 +4,12 @@ import subprocess
 import json
 from application_client.response_unpacker import unpack_get_public_key_response, unpack_sign_tx_response, unpack_sign_hash_response, Errors
 from ragger.navigator import NavInsID, NavIns
-from utils import ROOT_SCREENSHOT_PATH, read_lines_from_file, call_external_binary
+from utils import ROOT_SCREENSHOT_PATH, read_lines_from_file, call_external_binary, get_setting_position
 from ragger.firmware import Firmware
+from ragger.error import ExceptionRAPDU
 
 CHECK_SIGNATURE_BINARY_PATH = "tools/check-signature/target/debug/check-signature"
 
-def get_setting_position(firmware: Firmware, setting_idx: int, per_page: int) -> tuple[int, int]:
-    if firmware == Firmware.STAX:
-        screen_height = 672  # px
-        screen_width = 400  # px
-        header_height = 88  # px
-        footer_height = 92  # px
-    elif firmware == Firmware.FLEX:
-        screen_height = 600  # px
-        screen_width = 480  # px
-        header_height = 96  # px
-        footer_height = 96  # px
-    elif firmware == Firmware.APEX_P:
-        screen_height = 400  # px
-        screen_width = 300  # px
-        header_height = 60  # px
-        footer_height = 96  # px
-
-    index_in_page = setting_idx % per_page
-    usable_height = screen_height - (header_height + footer_height)
-    setting_height = usable_height // per_page
-    offset = (setting_height * index_in_page) + (setting_height // 2)
-    return screen_width // 2, header_height + offset
-
 # In those tests we check the behavior of the device when asked to sign a Tx (clear or blind signing)
 
 # In this test we send to the device a hash to sign and validate it on screen
@@ -460,3 +438,25 @@ def test_sign_hash_4(firmware, backend, navigator, test_name):
         print("Standard Error:")
         print(stderr)
         assert(False)
+
+# In this test we send a hash to sign while blind signing is disabled in settings
+def test_sign_hash_blind_signing_disabled(firmware, backend, navigator, test_name):
+
+    file_path = 'samples/apdu/hash_pedersen_0.dat'
+    all_apdus = read_lines_from_file(file_path)
+
+    # send all apdus except last one
+    for apdu in all_apdus[:-1]:
+        backend.exchange_raw(bytes.fromhex(apdu))
+
+    with pytest.raises(ExceptionRAPDU) as e:
+        with backend.exchange_async_raw(bytes.fromhex(all_apdus[-1])):
+            if firmware.device.startswith("nano"):
+                navigator.navigate_and_compare(ROOT_SCREENSHOT_PATH,
+                                               test_name,
+                                               [NavInsID.BOTH_CLICK])
+            else:
+                navigator.navigate_and_compare(ROOT_SCREENSHOT_PATH,
+                                               test_name,
+                                               [NavInsID.USE_CASE_CHOICE_REJECT])
+    assert e.value.status == Errors.SW_BLIND_SIGNING_DISABLED
@@ -1,4 +1,5 @@
 from pathlib import Path
+from ragger.firmware import Firmware
 from hashlib import sha256
 
 import subprocess
@@ -31,6 +32,31 @@ def call_external_binary(binary_path, *args):
         return None, e.stderr
 
 
+# Position of the switch of a setting on the NBGL settings page
+def get_setting_position(firmware: Firmware, setting_idx: int, per_page: int) -> tuple[int, int]:
+    if firmware == Firmware.STAX:
+        screen_height = 672  # px
+        screen_width = 400  # px
+        header_height = 88  # px
+        footer_height = 92  # px
+    elif firmware == Firmware.FLEX:
+        screen_height = 600  # px
+        screen_width = 480  # px
+        header_height = 96  # px
+        footer_height = 96  # px
+    elif firmware == Firmware.APEX_P:
+        screen_height = 400  # px
+        screen_width = 300  # px
+        header_height = 60  # px
+        footer_height = 96  # px
+
+    index_in_page = setting_idx % per_page
+    usable_height = screen_height - (header_height + footer_height)
+    setting_height = usable_height // per_page
+    offset = (setting_height * index_in_page) + (setting_height // 2)
+    return screen_width // 2, header_height + offset
+
+
 # Check if a signature of a given message is valid
 #def check_signature_validity(public_key: bytes, signature: bytes, message: bytes) -> bool:
 #    pk: VerifyingKey = VerifyingKey.from_string(
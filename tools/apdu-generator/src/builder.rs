@@ -1,9 +1,14 @@
 use crate::apdu::{Apdu, ApduHeader};
 use crate::types::{
-    Call, DeployAccountV1, DeployAccountV3, Ins, InvokeV1, InvokeV3, ResourceBounds,
+    Call, DeclareV3, DeployAccountV1, DeployAccountV3, Ins, InvokeV1, InvokeV3, ResourceBounds,
+    TypedData,
 };
+use serde_json::Value;
+use starknet::core::utils::get_selector_from_name;
 use starknet_types_core::felt::Felt;
 
+const TYPED_DATA_CHUNK_SIZE: usize = 250;
+
 pub enum ApduError {
     InternalError,
 }
@@ -159,6 +164,42 @@ pub fn tx_fields_deploy_v3(tx: &DeployAccountV3, cla: u8, ins: Ins, p1: u8) -> A
     apdu
 }
 
+pub fn tx_fields_declare_v3(tx: &DeclareV3, cla: u8, ins: Ins, p1: u8) -> Apdu {
+    let apdu_header = ApduHeader {
+        cla: cla,
+        ins: ins.into(),
+        p1,
+        p2: 0x00,
+    };
+    let mut apdu = Apdu::new(apdu_header);
+
+    let mut fe = Felt::from_hex_unchecked(&tx.sender_address);
+    let mut data = fe.to_bytes_be();
+    apdu.append(data.as_slice()).unwrap();
+
+    fe = Felt::from_hex_unchecked(&tx.chain_id);
+    data = fe.to_bytes_be();
+    apdu.append(data.as_slice()).unwrap();
+
+    fe = Felt::from_hex_unchecked(&tx.nonce);
+    data = fe.to_bytes_be();
+    apdu.append(data.as_slice()).unwrap();
+
+    fe = Felt::from_hex_unchecked(&tx.data_availability_mode);
+    data = fe.to_bytes_be();
+    apdu.append(data.as_slice()).unwrap();
+
+    fe = Felt::from_hex_unchecked(&tx.class_hash);
+    data = fe.to_bytes_be();
+    apdu.append(data.as_slice()).unwrap();
+
+    fe = Felt::from_hex_unchecked(&tx.compiled_class_hash);
+    data = fe.to_bytes_be();
+    apdu.append(data.as_slice()).unwrap();
+
+    apdu
+}
+
 pub fn tx_fields_deploy_v1(tx: &DeployAccountV1, cla: u8, ins: Ins, p1: u8) -> Apdu {
     let apdu_header = ApduHeader {
         cla: cla,
@@ -246,7 +287,7 @@ pub fn tx_fees(tip: &str, resources: &ResourceBounds, cla: u8, ins: Ins, p1: u8)
     // L1 Data Gas
     match &resources.l1_data_gas {
         Some(fee) => {
-            resource_buffer[2..8].copy_from_slice(b"L1_DATA");
+            resource_buffer[1..8].copy_from_slice(b"L1_DATA");
             resource_buffer[8..16].copy_from_slice(
                 &u64::from_str_radix(fee.max_amount.trim_start_matches("0x"), 16)
                     .unwrap()
@@ -264,26 +305,38 @@ pub fn tx_fees(tip: &str, resources: &ResourceBounds, cla: u8, ins: Ins, p1: u8)
     apdu
 }
 
-pub fn paymaster_data(_data: &[String], cla: u8, ins: Ins, p1: u8) -> Apdu {
-    let apdu_header = ApduHeader {
-        cla: cla,
-        ins: ins.into(),
-        p1,
-        p2: 0x00,
-    };
-    let apdu = Apdu::new(apdu_header);
-    apdu
+pub fn paymaster_data(data: &[String], cla: u8, ins: Ins, p1: u8) -> Vec<Apdu> {
+    data_chunks(data, cla, ins, p1)
 }
 
-pub fn accound_deployment_data(_tx: &[String], cla: u8, ins: Ins, p1: u8) -> Apdu {
-    let apdu_header = ApduHeader {
-        cla: cla,
-        ins: ins.into(),
-        p1,
-        p2: 0x00,
-    };
-    let apdu = Apdu::new(apdu_header);
-    apdu
+pub fn accound_deployment_data(data: &[String], cla: u8, ins: Ins, p1: u8) -> Vec<Apdu> {
+    data_chunks(data, cla, ins, p1)
+}
+
+/// Felt list sent 7 felts per APDU, p2 = 0x01 when more chunks follow, 0x00 for the last one
+fn data_chunks(data: &[String], cla: u8, ins: Ins, p1: u8) -> Vec<Apdu> {
+    let mut apdu_list: Vec<Apdu> = Vec::new();
+
+    let mut chunks: Vec<&[String]> = data.chunks(7).collect();
+    if chunks.is_empty() {
+        chunks.push(&[]);
+    }
+    let nb_chunks = chunks.len();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let apdu_header = ApduHeader {
+            cla: cla,
+            ins: ins.into(),
+            p1,
+            p2: if i + 1 < nb_chunks { 0x01 } else { 0x00 },
+        };
+        let mut apdu = Apdu::new(apdu_header);
+        for d in chunk.iter() {
+            let fe = Felt::from_hex_unchecked(d);
+            let _ = apdu.append(fe.to_bytes_be().as_slice()).unwrap();
+        }
+        apdu_list.push(apdu);
+    }
+    apdu_list
 }
 
 pub fn calls_nb(calls: &[Call], cla: u8, ins: Ins, p1: u8) -> Apdu {
@@ -408,3 +461,180 @@ pub fn constructor_calldata(calldata: &[String], cla: u8, ins: Ins, p1: u8) -> V
     }
     apdu_list
 }
+
+/// Convert a typed data JSON value to a Felt (hex string, decimal string, shortstring, number or bool)
+fn typed_data_felt(value: &Value) -> Felt {
+    match value {
+        Value::Number(n) => Felt::from(n.as_u64().unwrap()),
+        Value::Bool(b) => Felt::from(*b as u8),
+        Value::String(s) => {
+            if s.starts_with("0x") {
+                Felt::from_hex_unchecked(s)
+            } else if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+                Felt::from_dec_str(s).unwrap()
+            } else {
+                Felt::from_bytes_be_slice(s.as_bytes())
+            }
+        }
+        _ => panic!("Invalid typed data value {}", value),
+    }
+}
+
+/// Split data prefixed with its length (2 bytes) into APDUs
+fn typed_data_chunks(data: &[u8], cla: u8, ins: Ins, p1: u8) -> Vec<Apdu> {
+    let mut buffer = (data.len() as u16).to_be_bytes().to_vec();
+    buffer.extend_from_slice(data);
+
+    buffer
+        .chunks(TYPED_DATA_CHUNK_SIZE)
+        .map(|chunk| {
+            let apdu_header = ApduHeader {
+                cla,
+                ins: ins.into(),
+                p1,
+                p2: 0x00,
+            };
+            let mut apdu = Apdu::new(apdu_header);
+            apdu.append(chunk).unwrap();
+            apdu
+        })
+        .collect()
+}
+
+fn typed_data_value(
+    td: &TypedData,
+    type_name: &str,
+    value: &Value,
+    cla: u8,
+    ins: Ins,
+    p1: u8,
+) -> Vec<Apdu> {
+    let mut apdu_list: Vec<Apdu> = Vec::new();
+    let apdu_header = ApduHeader {
+        cla,
+        ins: ins.into(),
+        p1,
+        p2: 0x00,
+    };
+
+    if let Some(elem_type) = type_name.strip_suffix('*') {
+        let elements = value.as_array().unwrap();
+        let mut apdu = Apdu::new(apdu_header);
+        apdu.append(&Felt::from(elements.len()).to_bytes_be())
+            .unwrap();
+        apdu_list.push(apdu);
+        for element in elements {
+            apdu_list.append(&mut typed_data_value(td, elem_type, element, cla, ins, p1));
+        }
+    } else if td.is_enum(type_name) {
+        let variants = &td.types[type_name];
+        let (name, params) = value.as_object().unwrap().iter().next().unwrap();
+        let index = variants.iter().position(|v| v.name == *name).unwrap();
+        let mut apdu = Apdu::new(apdu_header);
+        apdu.append(&Felt::from(index).to_bytes_be()).unwrap();
+        apdu_list.push(apdu);
+
+        let variant_type = &variants[index].r#type;
+        let param_types = variant_type[1..variant_type.len() - 1]
+            .split(',')
+            .filter(|t| !t.is_empty());
+        for (i, param_type) in param_types.enumerate() {
+            apdu_list.append(&mut typed_data_value(
+                td, param_type, &params[i], cla, ins, p1,
+            ));
+        }
+    } else if let Some(members) = td.types.get(type_name) {
+        for m in members {
+            apdu_list.append(&mut typed_data_value(
+                td,
+                m.type_name(),
+                &value[&m.name],
+                cla,
+                ins,
+                p1,
+            ));
+        }
+    } else {
+        match type_name {
+            // Revision 0 strings are short strings
+            "string" if !td.is_legacy() => {
+                apdu_list.append(&mut typed_data_chunks(
+                    value.as_str().unwrap().as_bytes(),
+                    cla,
+                    ins,
+                    p1,
+                ));
+            }
+            "u256" => {
+                let low = typed_data_felt(&value["low"]).to_bytes_be();
+                let high = typed_data_felt(&value["high"]).to_bytes_be();
+                let mut apdu = Apdu::new(apdu_header);
+                apdu.append(&high[16..]).unwrap();
+                apdu.append(&low[16..]).unwrap();
+                apdu_list.push(apdu);
+            }
+            "selector" if !value.as_str().unwrap().starts_with("0x") => {
+                let selector = get_selector_from_name(value.as_str().unwrap()).unwrap();
+                let mut apdu = Apdu::new(apdu_header);
+                apdu.append(&selector.to_bytes_be()).unwrap();
+                apdu_list.push(apdu);
+            }
+            _ => {
+                let mut apdu = Apdu::new(apdu_header);
+                apdu.append(&typed_data_felt(value).to_bytes_be()).unwrap();
+                apdu_list.push(apdu);
+            }
+        }
+    }
+    apdu_list
+}
+
+/// Build the APDUs of a SNIP-12 typed data (account address, type definitions, domain and message values)
+pub fn typed_data(td: &TypedData, cla: u8, ins: Ins) -> Vec<Apdu> {
+    let mut apdu_list: Vec<Apdu> = Vec::new();
+
+    // Account address
+    let apdu_header = ApduHeader {
+        cla,
+        ins: ins.into(),
+        p1: 1,
+        p2: 0x00,
+    };
+    let mut apdu = Apdu::new(apdu_header);
+    apdu.append(&Felt::from_hex_unchecked(&td.account).to_bytes_be())
+        .unwrap();
+    apdu_list.push(apdu);
+
+    // Type definitions
+    for (name, members) in td.types.iter() {
+        let mut data: Vec<u8> = vec![td.is_enum(name) as u8, name.len() as u8];
+        data.extend_from_slice(name.as_bytes());
+        data.push(members.len() as u8);
+        for m in members {
+            data.push(m.name.len() as u8);
+            data.extend_from_slice(m.name.as_bytes());
+            data.push(m.type_name().len() as u8);
+            data.extend_from_slice(m.type_name().as_bytes());
+        }
+        apdu_list.append(&mut typed_data_chunks(&data, cla, ins, 2));
+    }
+
+    // Domain then message
+    for (type_name, value) in [
+        (td.domain_type(), &td.domain),
+        (td.primary_type.as_str(), &td.message),
+    ] {
+        let apdu_header = ApduHeader {
+            cla,
+            ins: ins.into(),
+            p1: 3,
+            p2: 0x00,
+        };
+        let mut apdu = Apdu::new(apdu_header);
+        apdu.append(type_name.as_bytes()).unwrap();
+        apdu_list.push(apdu);
+
+        apdu_list.append(&mut typed_data_value(td, type_name, value, cla, ins, 4));
+    }
+    apdu_list
+}
@@ -7,7 +7,7 @@ use std::{fs::File, path::Path};
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// JSON input file: Derivation path or Hash or Tx in JSON format
+    /// JSON input file: Derivation path or Hash or Tx or Typed data in JSON format
     #[arg(short, long)]
     json: String,
 
@@ -19,7 +19,10 @@ struct Args {
 use apdu_generator::{
     apdu::Apdu,
     builder,
-    types::{DeployAccountV1, DeployAccountV3, Dpath, Hash, Ins, InvokeV1, InvokeV3, Tx},
+    types::{
+        DeclareV3, DeployAccountV1, DeployAccountV3, Dpath, Hash, Ins, InvokeV1, InvokeV3, Tx,
+        TypedData,
+    },
 };
 
 const DPATH: &str = "m/2645'/1195502025'/1148870696'/0'/0'/0";
@@ -46,11 +49,18 @@ fn main() {
 
         let apdu = builder::hash_to_apdu(&hash.hash, args.cla, Ins::SignHash, 1, true);
         apdus.push(apdu.clone());
+    } else if let Ok(td) = serde_json::from_str::<TypedData>(&data) {
+        let dpath_apdu = builder::derivation_path(DPATH, args.cla, Ins::SignTypedData, 0);
+        apdus.push(dpath_apdu.clone());
+
+        let mut td_apdus = builder::typed_data(&td, args.cla, Ins::SignTypedData);
+        apdus.append(&mut td_apdus);
     } else if let Ok(tx) = serde_json::from_str::<InvokeV3>(&data)
         .map(Tx::V3)
         .or_else(|_| serde_json::from_str::<InvokeV1>(&data).map(Tx::V1))
         .or_else(|_| serde_json::from_str::<DeployAccountV3>(&data).map(Tx::DeployV3))
         .or_else(|_| serde_json::from_str::<DeployAccountV1>(&data).map(Tx::DeployV1))
+        .or_else(|_| serde_json::from_str::<DeclareV3>(&data).map(Tx::DeclareV3))
     {
         match tx {
             Tx::V1(mut tx) => {
@@ -80,17 +90,17 @@ fn main() {
                     builder::tx_fees(&tx.tip, &tx.resource_bounds, args.cla, Ins::SignTx, 2);
                 apdus.push(fees_apdu.clone());
 
-                let tx_data_apdu =
+                let mut paymaster_apdus =
                     builder::paymaster_data(&tx.paymaster_data, args.cla, Ins::SignTx, 3);
-                apdus.push(tx_data_apdu.clone());
+                apdus.append(&mut paymaster_apdus);
 
-                let tx_data_apdu = builder::accound_deployment_data(
+                let mut account_deployment_apdus = builder::accound_deployment_data(
                     &tx.account_deployment_data,
                     args.cla,
                     Ins::SignTx,
                     4,
                 );
-                apdus.push(tx_data_apdu.clone());
+                apdus.append(&mut account_deployment_apdus);
 
                 let tx_data_apdu = builder::calls_nb(&tx.calls, args.cla, Ins::SignTx, 5);
                 apdus.push(tx_data_apdu.clone());
@@ -120,13 +130,13 @@ fn main() {
                 );
                 apdus.push(fees_apdu.clone());
 
-                let paymaster_apdu = builder::paymaster_data(
+                let mut paymaster_apdus = builder::paymaster_data(
                     &tx.paymaster_data,
                     args.cla,
                     Ins::SignDeployAccount,
                     3,
                 );
-                apdus.push(paymaster_apdu.clone());
+                apdus.append(&mut paymaster_apdus);
 
                 let mut constructor_calldata_apdus = builder::constructor_calldata(
                     &tx.constructor_calldata,
@@ -153,6 +163,30 @@ fn main() {
                 );
                 apdus.append(&mut constructor_calldata_apdus);
             }
+            Tx::DeclareV3(tx) => {
+                let dpath_apdu = builder::derivation_path(DPATH, args.cla, Ins::SignDeclare, 0);
+                apdus.push(dpath_apdu.clone());
+
+                let tx_data_apdu =
+                    builder::tx_fields_declare_v3(&tx, args.cla, Ins::SignDeclare, 1);
+                apdus.push(tx_data_apdu.clone());
+
+                let fees_apdu =
+                    builder::tx_fees(&tx.tip, &tx.resource_bounds, args.cla, Ins::SignDeclare, 2);
+                apdus.push(fees_apdu.clone());
+
+                let mut paymaster_apdus =
+                    builder::paymaster_data(&tx.paymaster_data, args.cla, Ins::SignDeclare, 3);
+                apdus.append(&mut paymaster_apdus);
+
+                let mut account_deployment_apdus = builder::accound_deployment_data(
+                    &tx.account_deployment_data,
+                    args.cla,
+                    Ins::SignDeclare,
+                    4,
+                );
+                apdus.append(&mut account_deployment_apdus);
+            }
         }
     } else {
         panic!("Invalid input format");
@@ -1,6 +1,8 @@
 use serde::Deserialize;
+use serde_json::Value;
 use starknet::core::utils::get_selector_from_name;
 use starknet_types_core::felt::Felt;
+use std::collections::BTreeMap;
 use std::vec::Vec;
 
 #[derive(Copy, Clone)]
@@ -12,6 +14,8 @@ pub enum Ins {
     SignTxV1,
     SignDeployAccount,
     SignDeployAccountV1,
+    SignTypedData,
+    SignDeclare,
     Unknown,
 }
 
@@ -25,6 +29,8 @@ impl From<Ins> for u8 {
             Ins::SignTxV1 => 4u8,
             Ins::SignDeployAccount => 5u8,
             Ins::SignDeployAccountV1 => 6u8,
+            Ins::SignTypedData => 8u8,
+            Ins::SignDeclare => 11u8,
             Ins::Unknown => 0xff,
         }
     }
@@ -40,7 +46,9 @@ impl From<u8> for Ins {
             4 => Ins::SignTxV1,
             5 => Ins::SignDeployAccount,
             6 => Ins::SignDeployAccountV1,
-            7.. => Ins::Unknown,
+            8 => Ins::SignTypedData,
+            11 => Ins::SignDeclare,
+            _ => Ins::Unknown,
         }
     }
 }
@@ -115,6 +123,7 @@ pub enum Tx {
     V3(InvokeV3),
     DeployV1(DeployAccountV1),
     DeployV3(DeployAccountV3),
+    DeclareV3(DeclareV3),
 }
 
 #[derive(Deserialize, Debug)]
@@ -132,6 +141,21 @@ pub struct DeployAccountV3 {
     pub constructor_calldata: Vec<String>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct DeclareV3 {
+    pub version: String,
+    pub sender_address: String,
+    pub tip: String,
+    pub resource_bounds: ResourceBounds,
+    pub paymaster_data: Vec<String>,
+    pub chain_id: String,
+    pub nonce: String,
+    pub data_availability_mode: String,
+    pub account_deployment_data: Vec<String>,
+    pub class_hash: String,
+    pub compiled_class_hash: String,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct DeployAccountV1 {
     pub version: String,
@@ -159,3 +183,51 @@ pub struct Hash {
 pub struct Dpath {
     pub dpath_getpubkey: String,
 }
+
+#[derive(Deserialize, Debug)]
+pub struct TypedDataMember {
+    pub name: String,
+    pub r#type: String,
+    pub contains: Option<String>,
+}
+
+impl TypedDataMember {
+    /// Type as sent to the device: enum members are referenced by the enum name
+    pub fn type_name(&self) -> &str {
+        match (self.r#type.as_str(), &self.contains) {
+            ("enum", Some(name)) => name,
+            (t, _) => t,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+    pub types: BTreeMap<String, Vec<TypedDataMember>>,
+    pub primary_type: String,
+    pub domain: Value,
+    pub account: String,
+    pub message: Value,
+}
+
+impl TypedData {
+    /// Domain separator type name: `StarkNetDomain` for revision 0, `StarknetDomain` for revision 1
+    pub fn domain_type(&self) -> &str {
+        match self.types.contains_key("StarkNetDomain") {
+            true => "StarkNetDomain",
+            false => "StarknetDomain",
+        }
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        self.domain_type() == "StarkNetDomain"
+    }
+
+    pub fn is_enum(&self, name: &str) -> bool {
+        match self.types.get(name) {
+            Some(members) => members.iter().any(|m| m.r#type.starts_with('(')),
+            None => false,
+        }
+    }
+}